@@ -0,0 +1,60 @@
+//! Direct Bernstein evaluation against forward differencing for the default 1000
+//! ticks per curve. Run with `cargo bench --bench forward_differences`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use svg_to_lines::scalar::{Fixed, Scalar};
+use svg_to_lines::svg::forward_differences::ForwardDifferences;
+use svg_to_lines::svg::math::{CubicCurve, CurvePoint};
+use svg_to_lines::svg::point::Point;
+
+const TICKS: usize = 1000;
+const CURVES: usize = 2000;
+
+fn control_points() -> [Point; 4] {
+    [
+        Point::new(10., 20.),
+        Point::new(120., -40.),
+        Point::new(-30., 160.),
+        Point::new(90., 110.),
+    ]
+}
+
+fn direct<S: Scalar>() -> Duration {
+    let [start, p1, p2, end] = control_points();
+    let curve = CubicCurve::new(start.cast::<S>(), p1.cast(), p2.cast(), end.cast());
+    let started = Instant::now();
+    for _ in 0..CURVES {
+        for tick in 1..=TICKS {
+            black_box(curve.at(S::from_ratio(tick, TICKS)));
+        }
+    }
+    started.elapsed()
+}
+
+fn forward_differences<S: Scalar>() -> Duration {
+    let [start, p1, p2, end] = control_points();
+    let started = Instant::now();
+    for _ in 0..CURVES {
+        for point in ForwardDifferences::<S>::cubic(start, p1, p2, end, TICKS) {
+            black_box(point);
+        }
+    }
+    started.elapsed()
+}
+
+fn report(name: &str, direct: Duration, forward_differences: Duration) {
+    let points = (CURVES * TICKS) as f64;
+    println!(
+        "{:>5}: direct {:6.2} ns/point, forward differences {:6.2} ns/point",
+        name,
+        direct.as_nanos() as f64 / points,
+        forward_differences.as_nanos() as f64 / points,
+    );
+}
+
+fn main() {
+    report("f64", direct::<f64>(), forward_differences::<f64>());
+    report("f32", direct::<f32>(), forward_differences::<f32>());
+    report("fixed", direct::<Fixed>(), forward_differences::<Fixed>());
+}
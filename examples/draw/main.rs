@@ -10,7 +10,7 @@ use skulpin::{AppHandler, CoordinateSystem};
 use std::ffi::CString;
 
 use drawing_robot::svg::point::Point;
-use drawing_robot::svg::svg_curve::{points_from_path_segments, LineTo};
+use drawing_robot::svg::svg_curve::{try_points_from_path_segments, LineTo};
 use std::collections::LinkedList;
 
 fn points_to_draw() -> impl Iterator<Item = LineTo> {
@@ -325,9 +325,8 @@ fn points_to_draw() -> impl Iterator<Item = LineTo> {
 						c-4.641,23.668-6.033,30.938-7.425,50.121C602.657,607.656,639.784,536.033,623.696,521.338z";
 
     let path_parser = svgtypes::PathParser::from(svg_string);
-    let path_segments = path_parser.filter_map(Result::ok).into_iter();
-
-    points_from_path_segments(path_segments)
+    try_points_from_path_segments(path_parser)
+        .map(|line_to| line_to.unwrap_or_else(|error| panic!("broken path data, {}", error)))
 }
 
 fn main() {
@@ -414,6 +413,29 @@ impl AppHandler for ExampleApp {
                 }
 
                 LineTo::Erase(_) => {}
+
+                LineTo::Arc(arc) => {
+                    let (cx, cy, r) = (arc.center.x as f32, arc.center.y as f32, arc.radius as f32);
+                    let angle_of = |point: &Point| {
+                        ((point.y as f32 - cy).atan2(point.x as f32 - cx)).to_degrees()
+                    };
+                    let start_angle = angle_of(&prev_point);
+                    // with y pointing down skia's positive sweep is clockwise on screen
+                    let mut sweep = angle_of(&arc.end) - start_angle;
+                    if arc.clockwise && sweep <= 0. {
+                        sweep += 360.;
+                    } else if !arc.clockwise && sweep >= 0. {
+                        sweep -= 360.;
+                    }
+                    canvas.draw_arc(
+                        skia_safe::Rect::new(cx - r, cy - r, cx + r, cy + r),
+                        start_angle,
+                        sweep,
+                        false,
+                        &paint,
+                    );
+                    prev_point = arc.end;
+                }
             }
         }
     }
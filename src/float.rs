@@ -0,0 +1,46 @@
+//! Float functions that `core` does not provide, backed by `std` or, with the `libm`
+//! feature, by the pure Rust `libm` crate for bare metal targets.
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("either the `std` or the `libm` feature has to be enabled");
+
+macro_rules! float_functions {
+    ($($float:ty: $($name:ident => $libm_name:ident),*;)*) => {
+        $($(
+            #[cfg(feature = "libm")]
+            #[inline]
+            pub fn $libm_name(x: $float) -> $float {
+                libm::$libm_name(x)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            #[inline]
+            pub fn $libm_name(x: $float) -> $float {
+                x.$name()
+            }
+        )*)*
+    };
+}
+
+float_functions!(
+    f64: sqrt => sqrt, sin => sin, cos => cos, tan => tan, acos => acos, ceil => ceil, round => round, sinh => sinh;
+    f32: sqrt => sqrtf, sin => sinf, cos => cosf;
+);
+
+// only the grids of the heap-backed modules round down
+#[cfg(feature = "alloc")]
+float_functions!(
+    f64: floor => floor;
+);
+
+#[cfg(feature = "libm")]
+#[inline]
+pub fn atan2(y: f64, x: f64) -> f64 {
+    libm::atan2(y, x)
+}
+
+#[cfg(not(feature = "libm"))]
+#[inline]
+pub fn atan2(y: f64, x: f64) -> f64 {
+    y.atan2(x)
+}
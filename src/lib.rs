@@ -1,4 +1,13 @@
 #![allow(unused_parens)]
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod float;
+pub mod robot;
+pub mod scalar;
 pub mod svg;
@@ -1,6 +1,5 @@
 #![no_std]
 
-use svgtypes::PathSegment;
-mod svg;
+use svg_to_lines as _;
 
 fn main() {}
@@ -0,0 +1,99 @@
+//! The four-cable robot: a pen holder hung from four lines on a magnet board, each
+//! wound on the spool of a stepper motor.
+
+use super::kinematics::Kinematics;
+use crate::svg::math::distance;
+use crate::svg::point::Point;
+
+/// One line of the robot. Lengths are measured from `anchor`, where the line leaves its
+/// spool or pulley, to `attachment`, where it is tied to the pen holder, given relative
+/// to the pen tip.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cable {
+    pub anchor: Point,
+    pub attachment: Point,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FourCable {
+    pub cables: [Cable; 4],
+    /// How close the pen may get to the edge of the area the lines span. At the edge
+    /// two lines pull in a straight line against each other and nothing holds the pen
+    /// across it.
+    pub margin: f64,
+}
+
+impl FourCable {
+    pub fn new(cables: [Cable; 4]) -> Self {
+        FourCable { cables, margin: 0. }
+    }
+
+    /// Whether the lines can hold the pen at `pen`: it has to be inside the
+    /// quadrilateral of the anchors, each moved by its attachment offset, and at least
+    /// `margin` away from its sides.
+    pub fn is_reachable(&self, pen: Point) -> bool {
+        let corners = self.cables.map(|cable| cable.anchor - cable.attachment);
+        let mut sides = hull_sides(corners).peekable();
+        sides.peek().is_some()
+            && sides.all(|(start, end, inside)| {
+                let side = end - start;
+                let offset = pen - start;
+                let towards_inside = (side.x * offset.y - side.y * offset.x) * inside;
+                towards_inside >= self.margin * distance(start, end)
+            })
+    }
+
+    /// Line lengths in the order of `cables` with the pen at `pen`, `None` if it can not
+    /// be reached.
+    pub fn cable_lengths(&self, pen: Point) -> Option<[f64; 4]> {
+        if !self.is_reachable(pen) {
+            return None;
+        }
+        Some(
+            self.cables
+                .map(|cable| distance(cable.anchor, pen + cable.attachment)),
+        )
+    }
+}
+
+impl Kinematics<4> for FourCable {
+    fn lengths(&self, pen: Point) -> Option<[f64; 4]> {
+        self.cable_lengths(pen)
+    }
+
+    fn is_reachable(&self, pen: Point) -> bool {
+        FourCable::is_reachable(self, pen)
+    }
+}
+
+// === private members ===
+
+// the sides of the convex hull of the corners, each with the sign of the cross product
+// that points inside. Corners on a side count as inside, and corners in a straight
+// line have no sides.
+fn hull_sides(corners: [Point; 4]) -> impl Iterator<Item = (Point, Point, f64)> {
+    (0..4)
+        .flat_map(|first| (first + 1..4).map(move |second| (first, second)))
+        .filter_map(move |(first, second)| {
+            let (start, end) = (corners[first], corners[second]);
+            let side = end - start;
+            let mut sign = 0.;
+            for (index, corner) in corners.iter().enumerate() {
+                if index == first || index == second {
+                    continue;
+                }
+                let offset = *corner - start;
+                let cross = side.x * offset.y - side.y * offset.x;
+                if cross * sign < 0. {
+                    return None;
+                }
+                if cross != 0. {
+                    sign = cross.signum();
+                }
+            }
+            if sign == 0. {
+                return None;
+            }
+            Some((start, end, sign))
+        })
+}
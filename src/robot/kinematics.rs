@@ -0,0 +1,116 @@
+use crate::svg::line_to::LineTo;
+use crate::svg::math::length;
+use crate::svg::point::Point;
+
+// Gauss-Newton steps taken to find the pen for some line lengths
+const MAX_PEN_STEPS: usize = 32;
+// times a step is halved when it leaves the reachable area or fits the lengths worse
+const MAX_STEP_HALVINGS: usize = 16;
+// board units the pen is moved to see how the lengths change
+const PEN_NUDGE: f64 = 1e-4;
+// steps shorter than this, relative to the distance from the origin, end the search
+const PEN_SETTLED: f64 = 1e-12;
+
+/// The pen position a move asked for that the robot can not reach, and the index of
+/// the move in the stream.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Unreachable {
+    pub index: usize,
+    pub point: Point,
+}
+
+/// Maps pen positions on the board to the lengths of the `MOTORS` lines holding the
+/// pen. Board units, usually millimetres, with y pointing down as in SVG.
+pub trait Kinematics<const MOTORS: usize> {
+    /// Line lengths with the pen at `pen`, `None` if the robot can not hold it there.
+    fn lengths(&self, pen: Point) -> Option<[f64; MOTORS]>;
+
+    fn is_reachable(&self, pen: Point) -> bool {
+        self.lengths(pen).is_some()
+    }
+
+    /// Where the pen is with lines of `lengths`, solved from a starting guess `near` it:
+    /// the point whose lengths are closest, by least squares, to the ones given. Lengths
+    /// no pen position has exactly, such as four lines changed evenly, give the closest
+    /// fit; if the search does not settle, the best point found so far. `None` only if
+    /// `near` is out of reach.
+    fn pen_near(&self, lengths: [f64; MOTORS], near: Point) -> Option<Point> {
+        let misfit = |at: &[f64; MOTORS]| {
+            (0..MOTORS).fold(0., |sum, motor| {
+                let error = lengths[motor] - at[motor];
+                sum + error * error
+            })
+        };
+        // how the lengths change per unit of pen travel along `nudge`, measured the
+        // other way where the pen can not go
+        let rates = |pen: Point, at: &[f64; MOTORS], nudge: Point| {
+            let (there, scale) = match self.lengths(pen + nudge) {
+                Some(there) => (there, 1. / PEN_NUDGE),
+                None => (self.lengths(pen - nudge)?, -1. / PEN_NUDGE),
+            };
+            Some(core::array::from_fn::<f64, MOTORS, _>(|motor| {
+                (there[motor] - at[motor]) * scale
+            }))
+        };
+
+        let mut pen = near;
+        let mut at = self.lengths(pen)?;
+        for _ in 0..MAX_PEN_STEPS {
+            let (across, down) = match (
+                rates(pen, &at, Point::new(PEN_NUDGE, 0.)),
+                rates(pen, &at, Point::new(0., PEN_NUDGE)),
+            ) {
+                (Some(across), Some(down)) => (across, down),
+                _ => break,
+            };
+            // normal equations of the lengths linearised around the pen
+            let (mut xx, mut xy, mut yy, mut x, mut y) = (0., 0., 0., 0., 0.);
+            for motor in 0..MOTORS {
+                let error = lengths[motor] - at[motor];
+                xx += across[motor] * across[motor];
+                xy += across[motor] * down[motor];
+                yy += down[motor] * down[motor];
+                x += across[motor] * error;
+                y += down[motor] * error;
+            }
+            let determinant = xx * yy - xy * xy;
+            if determinant.abs() <= f64::EPSILON * (xx * yy).abs() {
+                break;
+            }
+            let mut step = Point::new(yy * x - xy * y, xx * y - xy * x) * (1. / determinant);
+
+            let mut next = None;
+            for _ in 0..MAX_STEP_HALVINGS {
+                match self.lengths(pen + step) {
+                    Some(there) if misfit(&there) <= misfit(&at) => {
+                        next = Some(there);
+                        break;
+                    }
+                    _ => step = step * 0.5,
+                }
+            }
+            match next {
+                Some(there) => {
+                    pen = pen + step;
+                    at = there;
+                }
+                None => break,
+            }
+            if length(step) <= PEN_SETTLED * (1. + length(pen)) {
+                break;
+            }
+        }
+        Some(pen)
+    }
+
+    /// Line lengths for the target of every move. Arcs only get their end point.
+    fn lengths_for_moves<'a>(
+        &'a self,
+        line_tos: impl Iterator<Item = LineTo> + 'a,
+    ) -> impl Iterator<Item = Result<[f64; MOTORS], Unreachable>> + 'a {
+        line_tos.enumerate().map(move |(index, line_to)| {
+            let point = line_to.target();
+            self.lengths(point).ok_or(Unreachable { index, point })
+        })
+    }
+}
@@ -0,0 +1,9 @@
+//! Turning pen positions on the board into what the motors of a drawing robot need.
+
+pub mod four_cable;
+pub mod kinematics;
+#[cfg(feature = "alloc")]
+pub mod planner;
+pub mod steps;
+pub mod subdivide;
+pub mod v_plotter;
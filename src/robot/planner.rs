@@ -0,0 +1,347 @@
+//! Timing pen moves so no motor is asked for more speed or acceleration than it has:
+//! every move speeds up, cruises and slows down again, the trapezoid of its speed
+//! over time.
+
+use alloc::vec::Vec;
+
+use super::kinematics::{Kinematics, Unreachable};
+use crate::float::{ceil, cos, sin, sqrt};
+use crate::svg::line_to::LineTo;
+use crate::svg::math::{angle_around, arc_sweep, clamp_ticks, distance};
+use crate::svg::point::Point;
+
+// arcs are followed in pieces turning by at most this many radians to find how fast
+// the lines change along them
+const ARC_PIECE_ANGLE: f64 = core::f64::consts::PI / 32.;
+
+/// Speeds in board units per second, accelerations in board units per second squared.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Limits<const MOTORS: usize> {
+    /// Pen speed along the board.
+    pub velocity: f64,
+    pub acceleration: f64,
+    /// How fast each line may be reeled in or out.
+    pub line_velocity: [f64; MOTORS],
+    pub line_acceleration: [f64; MOTORS],
+    /// The largest sudden change of speed at a corner, for the pen and for every line.
+    pub corner_jump: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlannerOptions<const MOTORS: usize> {
+    /// For `Draw` and `Erase` moves, and for arcs.
+    pub draw: Limits<MOTORS>,
+    pub fly: Limits<MOTORS>,
+}
+
+/// A move with the pen speeds it starts, cruises and ends at, and how long it spends
+/// speeding up, cruising and slowing down.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimedMove {
+    pub line_to: LineTo,
+    /// Distance the pen travels, along the curve for arcs.
+    pub length: f64,
+    pub entry: f64,
+    pub cruise: f64,
+    pub exit: f64,
+    /// Used for both speeding up and slowing down.
+    pub acceleration: f64,
+    pub accelerate_time: f64,
+    pub cruise_time: f64,
+    pub decelerate_time: f64,
+}
+
+impl TimedMove {
+    pub fn duration(&self) -> f64 {
+        self.accelerate_time + self.cruise_time + self.decelerate_time
+    }
+}
+
+/// Times every move of the stream with the pen starting at `start`, at rest, and ending
+/// at rest. Line speeds come from how much each line changes over a straight move, so
+/// long moves should be subdivided first (see `subdivide::subdivide_line_tos`); arcs are
+/// timed along the curve, with the line speeds taken at points along it. The pen stops
+/// wherever it is lifted or lowered. An unreachable `start` is reported against the
+/// first move.
+pub fn plan_line_tos<K: Kinematics<MOTORS>, const MOTORS: usize>(
+    kinematics: &K,
+    line_tos: impl IntoIterator<Item = LineTo>,
+    start: Point,
+    options: &PlannerOptions<MOTORS>,
+) -> Result<Vec<TimedMove>, Unreachable> {
+    let mut pen = start;
+    let mut pen_lengths = None;
+    let mut moves = Vec::new();
+    for (index, line_to) in line_tos.into_iter().enumerate() {
+        let unreachable = |point| Unreachable { index, point };
+        let start_lengths = match pen_lengths {
+            Some(lengths) => lengths,
+            None => kinematics.lengths(pen).ok_or(unreachable(pen))?,
+        };
+        let target = line_to.target();
+        let lengths = kinematics.lengths(target).ok_or(unreachable(target))?;
+        let limits = match line_to {
+            LineTo::Fly(_) => &options.fly,
+            _ => &options.draw,
+        };
+        let course = Course::new(line_to, pen);
+        moves.push(
+            Move::new(
+                line_to,
+                course,
+                [start_lengths, lengths],
+                kinematics,
+                limits,
+            )
+            .map_err(unreachable)?,
+        );
+        pen = target;
+        pen_lengths = Some(lengths);
+    }
+
+    // speeds at the start of each move, limited by the corner before it, then by
+    // braking for what comes after and by speeding up from what came before
+    let mut entries: Vec<f64> = (0..moves.len())
+        .map(|index| match index {
+            0 => 0.,
+            _ => corner_velocity(&moves[index - 1], &moves[index]),
+        })
+        .collect();
+    let mut exit = 0.;
+    for index in (0..moves.len()).rev() {
+        let reachable = sqrt(exit * exit + 2. * moves[index].acceleration * moves[index].length);
+        entries[index] = entries[index].min(reachable);
+        exit = entries[index];
+    }
+    for index in 0..moves.len() {
+        let reachable = sqrt(
+            entries[index] * entries[index] + 2. * moves[index].acceleration * moves[index].length,
+        );
+        if let Some(next) = entries.get_mut(index + 1) {
+            *next = next.min(reachable);
+        }
+    }
+
+    Ok(moves
+        .iter()
+        .enumerate()
+        .map(|(index, a_move)| {
+            let exit = entries.get(index + 1).copied().unwrap_or(0.);
+            a_move.timed(entries[index], exit)
+        })
+        .collect())
+}
+
+// === private members ===
+
+// the way the pen goes from the end of one move to the end of the next
+#[derive(Debug, Copy, Clone)]
+enum Course {
+    Straight {
+        start: Point,
+        end: Point,
+    },
+    Arc {
+        center: Point,
+        radius: f64,
+        /// Angle around the centre the arc starts at.
+        from: f64,
+        /// Signed angle it turns by, positive for clockwise arcs.
+        sweep: f64,
+    },
+}
+
+impl Course {
+    fn new(line_to: LineTo, start: Point) -> Self {
+        match line_to {
+            LineTo::Arc(arc) if arc.radius > 0. => {
+                let from = angle_around(arc.center, start);
+                let sweep = arc_sweep(angle_around(arc.center, arc.end) - from, arc.clockwise);
+                Course::Arc {
+                    center: arc.center,
+                    radius: arc.radius,
+                    from,
+                    sweep,
+                }
+            }
+            _ => Course::Straight {
+                start,
+                end: line_to.target(),
+            },
+        }
+    }
+
+    fn length(&self) -> f64 {
+        match *self {
+            Course::Straight { start, end } => distance(start, end),
+            Course::Arc { radius, sweep, .. } => radius * sweep.abs(),
+        }
+    }
+
+    // pieces the lines are checked over, each as long as the others
+    fn pieces(&self) -> usize {
+        match *self {
+            Course::Straight { .. } => 1,
+            Course::Arc { sweep, .. } => clamp_ticks(ceil(sweep.abs() / ARC_PIECE_ANGLE)),
+        }
+    }
+
+    // the point a fraction `time` of the way along
+    fn at(&self, time: f64) -> Point {
+        match *self {
+            Course::Straight { start, end } => start + (end - start) * time,
+            Course::Arc {
+                center,
+                radius,
+                from,
+                sweep,
+            } => {
+                let angle = from + sweep * time;
+                center + Point::new(cos(angle), sin(angle)) * radius
+            }
+        }
+    }
+
+    // unit vector of the pen's travel a fraction `time` of the way along
+    fn direction(&self, time: f64) -> Point {
+        match *self {
+            Course::Straight { start, end } => (end - start) * (1. / distance(start, end)),
+            Course::Arc { from, sweep, .. } => {
+                let angle = from + sweep * time;
+                let turn = if sweep > 0. { 1. } else { -1. };
+                Point::new(-sin(angle), cos(angle)) * turn
+            }
+        }
+    }
+}
+
+struct Move<const MOTORS: usize> {
+    line_to: LineTo,
+    length: f64,
+    /// Unit vectors of the pen's travel as the move starts and ends, zero for moves
+    /// that stay in place.
+    entry_direction: Point,
+    exit_direction: Point,
+    /// How far each line changes per unit of pen travel as the move starts and ends.
+    entry_rates: [f64; MOTORS],
+    exit_rates: [f64; MOTORS],
+    velocity: f64,
+    acceleration: f64,
+    corner_jump: f64,
+}
+
+impl<const MOTORS: usize> Move<MOTORS> {
+    // `Err` with a point along an arc the robot can not reach
+    fn new<K: Kinematics<MOTORS>>(
+        line_to: LineTo,
+        course: Course,
+        [start_lengths, end_lengths]: [[f64; MOTORS]; 2],
+        kinematics: &K,
+        limits: &Limits<MOTORS>,
+    ) -> Result<Self, Point> {
+        let length = course.length();
+        if length == 0. || !length.is_finite() {
+            return Ok(Move {
+                line_to,
+                length: 0.,
+                entry_direction: Point::ZERO,
+                exit_direction: Point::ZERO,
+                entry_rates: [0.; MOTORS],
+                exit_rates: [0.; MOTORS],
+                velocity: 0.,
+                acceleration: limits.acceleration,
+                corner_jump: limits.corner_jump,
+            });
+        }
+
+        // line rates over every piece, the steepest of them limiting the whole move
+        let pieces = course.pieces();
+        let piece_length = length / pieces as f64;
+        let mut from = start_lengths;
+        let mut entry_rates = [0.; MOTORS];
+        let mut exit_rates = [0.; MOTORS];
+        let mut velocity = limits.velocity;
+        let mut acceleration = limits.acceleration;
+        for piece in 1..=pieces {
+            let to = if piece == pieces {
+                end_lengths
+            } else {
+                let point = course.at(piece as f64 / pieces as f64);
+                kinematics.lengths(point).ok_or(point)?
+            };
+            exit_rates = core::array::from_fn(|motor| (to[motor] - from[motor]) / piece_length);
+            if piece == 1 {
+                entry_rates = exit_rates;
+            }
+            for (motor, rate) in exit_rates.iter().enumerate() {
+                velocity = velocity.min(limits.line_velocity[motor] / rate.abs());
+                acceleration = acceleration.min(limits.line_acceleration[motor] / rate.abs());
+            }
+            from = to;
+        }
+        Ok(Move {
+            line_to,
+            length,
+            entry_direction: course.direction(0.),
+            exit_direction: course.direction(1.),
+            entry_rates,
+            exit_rates,
+            velocity,
+            acceleration,
+            corner_jump: limits.corner_jump,
+        })
+    }
+
+    // the trapezoid, or the triangle for moves too short to reach the top speed
+    fn timed(&self, entry: f64, exit: f64) -> TimedMove {
+        let acceleration = self.acceleration;
+        if self.length == 0. {
+            return TimedMove {
+                line_to: self.line_to,
+                length: 0.,
+                entry,
+                cruise: entry,
+                exit,
+                acceleration,
+                accelerate_time: 0.,
+                cruise_time: 0.,
+                decelerate_time: 0.,
+            };
+        }
+
+        let peak = sqrt((2. * acceleration * self.length + entry * entry + exit * exit) / 2.);
+        let cruise = self.velocity.min(peak).max(entry).max(exit);
+        let accelerate_distance = (cruise * cruise - entry * entry) / (2. * acceleration);
+        let decelerate_distance = (cruise * cruise - exit * exit) / (2. * acceleration);
+        let cruise_distance = (self.length - accelerate_distance - decelerate_distance).max(0.);
+        TimedMove {
+            line_to: self.line_to,
+            length: self.length,
+            entry,
+            cruise,
+            exit,
+            acceleration,
+            accelerate_time: (cruise - entry) / acceleration,
+            cruise_time: cruise_distance / cruise,
+            decelerate_time: (cruise - exit) / acceleration,
+        }
+    }
+}
+
+// the fastest the pen may go from one move into the next without its direction, or the
+// speed of any line, changing by more than the corner jump at once
+fn corner_velocity<const MOTORS: usize>(before: &Move<MOTORS>, after: &Move<MOTORS>) -> f64 {
+    let lifts_or_lowers =
+        matches!(before.line_to, LineTo::Fly(_)) != matches!(after.line_to, LineTo::Fly(_));
+    if lifts_or_lowers {
+        return 0.;
+    }
+    let jump = before.corner_jump.min(after.corner_jump);
+    let mut velocity = before.velocity.min(after.velocity);
+    velocity = velocity.min(jump / distance(before.exit_direction, after.entry_direction));
+    for motor in 0..MOTORS {
+        let change = (after.entry_rates[motor] - before.exit_rates[motor]).abs();
+        velocity = velocity.min(jump / change);
+    }
+    velocity
+}
@@ -0,0 +1,128 @@
+//! Turning line lengths into step counts for the motors that wind the lines.
+
+use core::f64::consts::PI;
+
+use crate::float::round;
+
+/// Line winding on in layers, each one making the spool a little thicker, so that one
+/// turn moves more line the more is wound on.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BuildUp {
+    pub line_diameter: f64,
+    /// Space between the flanges of the spool, how wide one layer is.
+    pub spool_width: f64,
+    /// All the line on the spool and off it. What is off the spool is the line length
+    /// the kinematics gives.
+    pub line_length: f64,
+}
+
+/// A stepper motor and the spool it turns.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Drive {
+    /// Diameter of the bare spool.
+    pub spool_diameter: f64,
+    /// Full steps for one turn of the motor.
+    pub steps_per_revolution: u32,
+    pub microstepping: u32,
+    /// Motor turns for one turn of the spool.
+    pub gear_ratio: f64,
+    pub build_up: Option<BuildUp>,
+}
+
+impl Drive {
+    pub fn new(spool_diameter: f64, steps_per_revolution: u32, microstepping: u32) -> Self {
+        Drive {
+            spool_diameter,
+            steps_per_revolution,
+            microstepping,
+            gear_ratio: 1.,
+            build_up: None,
+        }
+    }
+
+    /// Microsteps for one turn of the spool.
+    pub fn steps_per_turn(&self) -> f64 {
+        self.steps_per_revolution as f64 * self.microstepping as f64 * self.gear_ratio
+    }
+
+    /// Spool turns paying out line until `length` is off the spool, counted from an
+    /// arbitrary zero, so only differences mean anything.
+    pub fn turns(&self, length: f64) -> f64 {
+        match self.build_up {
+            Some(build_up) if build_up.line_diameter > 0. && build_up.spool_width > 0. => {
+                -self.turns_to_wind(build_up.line_length - length, &build_up)
+            }
+            _ => length / (PI * self.spool_diameter),
+        }
+    }
+
+    // turns that wind `wound` of line onto the empty spool, layer by layer. Each layer
+    // lies on the one below, so the line in layer `k` runs at the bare diameter plus
+    // `2 * k + 1` line diameters.
+    fn turns_to_wind(&self, wound: f64, build_up: &BuildUp) -> f64 {
+        let turns_per_layer = (build_up.spool_width / build_up.line_diameter).max(1.);
+        let mut left = wound;
+        let mut turns = 0.;
+        let mut layer = 0;
+        loop {
+            let diameter = self.spool_diameter + (2 * layer + 1) as f64 * build_up.line_diameter;
+            let layer_length = turns_per_layer * PI * diameter;
+            if !left.is_finite() || left <= layer_length {
+                return turns + left / (PI * diameter);
+            }
+            left -= layer_length;
+            turns += turns_per_layer;
+            layer += 1;
+        }
+    }
+}
+
+/// Keeps track of where `MOTORS` motors are, in whole microsteps counted from a home
+/// position, and of the fraction of a step each one is off from its exact position.
+/// Positive steps pay out line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepConverter<const MOTORS: usize> {
+    drives: [Drive; MOTORS],
+    home_turns: [f64; MOTORS],
+    positions: [i64; MOTORS],
+    residuals: [f64; MOTORS],
+}
+
+impl<const MOTORS: usize> StepConverter<MOTORS> {
+    /// A converter with the motors at step 0 and the line lengths at `home`.
+    pub fn new(drives: [Drive; MOTORS], home: [f64; MOTORS]) -> Self {
+        let home_turns = core::array::from_fn(|motor| drives[motor].turns(home[motor]));
+        StepConverter {
+            drives,
+            home_turns,
+            positions: [0; MOTORS],
+            residuals: [0.; MOTORS],
+        }
+    }
+
+    /// Step targets for the motors to get the lines to `lengths`. Each motor moves by
+    /// the whole steps nearest to the exact change plus the fraction it was short of
+    /// after the last move, and keeps the new fraction for the next one, so rounding
+    /// never adds up over a long stream.
+    pub fn targets(&mut self, lengths: [f64; MOTORS]) -> [i64; MOTORS] {
+        for (motor, length) in lengths.iter().enumerate() {
+            let drive = &self.drives[motor];
+            let exact = (drive.turns(*length) - self.home_turns[motor]) * drive.steps_per_turn();
+            let wanted = exact - self.positions[motor] as f64;
+            let steps = round(wanted);
+            self.positions[motor] += steps as i64;
+            self.residuals[motor] = wanted - steps;
+        }
+        self.positions
+    }
+
+    pub fn positions(&self) -> [i64; MOTORS] {
+        self.positions
+    }
+
+    /// How far each motor is from its exact position, in steps, at most half a step
+    /// either way.
+    pub fn residuals(&self) -> [f64; MOTORS] {
+        self.residuals
+    }
+}
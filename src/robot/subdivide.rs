@@ -0,0 +1,122 @@
+//! Splitting straight moves so they stay straight on the board. Motors move the line
+//! lengths evenly from one point to the next, and with lines the pen does not follow a
+//! straight line when the lengths change evenly.
+
+use super::kinematics::Kinematics;
+use crate::svg::line_to::LineTo;
+use crate::svg::math::distance_to_segment;
+use crate::svg::point::Point;
+
+// no move is split into more pieces than this, however tight the tolerance
+const MAX_PIECES: usize = 1 << 12;
+// where each piece is checked, as fractions of it
+const SAMPLES: [f64; 3] = [0.25, 0.5, 0.75];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SubdivideOptions {
+    /// How far the pen may stray from the straight line while the line lengths change
+    /// evenly along a piece, in board units.
+    pub tolerance: f64,
+    /// Split `Fly` moves as well, for robots that must not hit anything on the way.
+    pub fly_moves: bool,
+}
+
+impl Default for SubdivideOptions {
+    fn default() -> Self {
+        SubdivideOptions {
+            tolerance: 0.1,
+            fly_moves: false,
+        }
+    }
+}
+
+/// Splits `Draw` and `Erase` moves, and `Fly` moves if asked, into equal pieces short
+/// enough that moving the line lengths evenly along each piece keeps the pen within
+/// the tolerance of the straight line, with the pen starting at `start`. Arcs are passed
+/// on as they are, and so are moves that go where the robot can not reach.
+pub fn subdivide_line_tos<'a, K: Kinematics<MOTORS>, const MOTORS: usize>(
+    kinematics: &'a K,
+    line_tos: impl Iterator<Item = LineTo> + 'a,
+    start: Point,
+    options: SubdivideOptions,
+) -> impl Iterator<Item = LineTo> + 'a {
+    let mut pen = start;
+    line_tos.flat_map(move |line_to| {
+        let start = pen;
+        let end = line_to.target();
+        pen = end;
+        let split = match line_to {
+            LineTo::Draw(_) | LineTo::Erase(_) => true,
+            LineTo::Fly(_) => options.fly_moves,
+            LineTo::Arc(_) => false,
+        };
+        let pieces = if split {
+            pieces(kinematics, start, end, options.tolerance)
+        } else {
+            1
+        };
+        (1..=pieces).map(move |piece| {
+            let point = start + (end - start) * (piece as f64 / pieces as f64);
+            match line_to {
+                _ if piece == pieces => line_to,
+                LineTo::Fly(_) => LineTo::Fly(point),
+                LineTo::Erase(_) => LineTo::Erase(point),
+                _ => LineTo::Draw(point),
+            }
+        })
+    })
+}
+
+// === private members ===
+
+// the fewest pieces, doubling from one, that keep every piece within the tolerance
+fn pieces<K: Kinematics<MOTORS>, const MOTORS: usize>(
+    kinematics: &K,
+    start: Point,
+    end: Point,
+    tolerance: f64,
+) -> usize {
+    if start == end || tolerance.is_nan() || tolerance <= 0. {
+        return 1;
+    }
+    let mut pieces = 1;
+    while pieces < MAX_PIECES {
+        match deviation(kinematics, start, end, pieces) {
+            Some(deviation) if deviation > tolerance => pieces *= 2,
+            _ => break,
+        }
+    }
+    pieces
+}
+
+// the farthest the pen gets from the straight line with the line lengths changing
+// evenly along each of `pieces` equal pieces, `None` if a point of the straight line
+// it is checked at is out of reach
+fn deviation<K: Kinematics<MOTORS>, const MOTORS: usize>(
+    kinematics: &K,
+    start: Point,
+    end: Point,
+    pieces: usize,
+) -> Option<f64> {
+    let at = |time: f64| start + (end - start) * time;
+    let mut largest: f64 = 0.;
+    let mut from = kinematics.lengths(start)?;
+    for piece in 0..pieces {
+        let (piece_start, piece_end) = (
+            piece as f64 / pieces as f64,
+            (piece + 1) as f64 / pieces as f64,
+        );
+        let to = kinematics.lengths(at(piece_end))?;
+        for sample in SAMPLES {
+            let mut even = from;
+            for motor in 0..MOTORS {
+                even[motor] += (to[motor] - from[motor]) * sample;
+            }
+            let straight = at(piece_start + (piece_end - piece_start) * sample);
+            let pen = kinematics.pen_near(even, straight)?;
+            largest = largest.max(distance_to_segment(start, end, pen));
+        }
+        from = to;
+    }
+    Some(largest)
+}
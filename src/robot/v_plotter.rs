@@ -0,0 +1,107 @@
+//! The V-plotter, or polargraph: a gondola holding the pen, hung from two lines that
+//! are wound on motors at the top of the board.
+
+use super::kinematics::Kinematics;
+use crate::float::{sinh, sqrt};
+use crate::svg::math::distance;
+use crate::svg::point::Point;
+
+/// What the lines and the gondola weigh, in any unit as long as it is the same for
+/// both. Heavier lines sag more, a heavier gondola pulls them straighter.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sag {
+    /// Weight of one board unit of line.
+    pub line_weight: f64,
+    /// Weight of the gondola with the pen.
+    pub gondola_weight: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VPlotter {
+    /// Where the left line leaves its spool or pulley.
+    pub left: Point,
+    /// Where the right line leaves its spool or pulley.
+    pub right: Point,
+    /// Distance between where the two lines are tied to the gondola, level with the pen
+    /// and centred on it.
+    pub gondola_width: f64,
+    /// How close the ends of the lines may get, across and down, to the motors. Near
+    /// the motors the lines pull almost straight against each other or the gondola
+    /// hangs from one line only.
+    pub margin: f64,
+    /// Lengthens the lines by how much they sag under their own weight, `None` for
+    /// taut lines.
+    pub sag: Option<Sag>,
+}
+
+impl VPlotter {
+    pub fn new(left: Point, right: Point, gondola_width: f64) -> Self {
+        VPlotter {
+            left,
+            right,
+            gondola_width,
+            margin: 0.,
+            sag: None,
+        }
+    }
+
+    /// Whether the lines can hold the pen at `pen`: both have to pull up and away from
+    /// each other, by at least `margin` each way.
+    pub fn is_reachable(&self, pen: Point) -> bool {
+        let (left, right) = self.spans(pen);
+        [left.x, left.y, right.x, right.y]
+            .iter()
+            .all(|span| *span > 0. && *span >= self.margin)
+    }
+
+    /// Left and right line length with the pen at `pen`, `None` if it can not be
+    /// reached.
+    pub fn line_lengths(&self, pen: Point) -> Option<[f64; 2]> {
+        if !self.is_reachable(pen) {
+            return None;
+        }
+        let (left, right) = self.spans(pen);
+        let sag = match self.sag {
+            Some(sag) if sag.line_weight > 0. => sag,
+            _ => {
+                let straight = |span: Point| distance(Point::ZERO, span);
+                return Some([straight(left), straight(right)]);
+            }
+        };
+
+        // the gondola hangs where the horizontal pulls of the lines cancel and their
+        // vertical pulls carry its weight. The angles are taken from the straight lines,
+        // which is close enough while the lines weigh much less than the gondola.
+        let horizontal_pull = sag.gondola_weight / (left.y / left.x + right.y / right.x);
+        let catenary = horizontal_pull / sag.line_weight;
+        let sagging = |span: Point| {
+            let across = 2. * catenary * sinh(span.x / (2. * catenary));
+            sqrt(span.y * span.y + across * across)
+        };
+        let lengths = [sagging(left), sagging(right)];
+        if lengths.iter().all(|length| length.is_finite()) {
+            Some(lengths)
+        } else {
+            None
+        }
+    }
+
+    // how far across and down each line runs, from its motor to the gondola, both
+    // positive where the line pulls the gondola up and towards its motor
+    fn spans(&self, pen: Point) -> (Point, Point) {
+        let half_width = self.gondola_width / 2.;
+        let left = Point::new(pen.x - half_width - self.left.x, pen.y - self.left.y);
+        let right = Point::new(self.right.x - (pen.x + half_width), pen.y - self.right.y);
+        (left, right)
+    }
+}
+
+impl Kinematics<2> for VPlotter {
+    fn lengths(&self, pen: Point) -> Option<[f64; 2]> {
+        self.line_lengths(pen)
+    }
+
+    fn is_reachable(&self, pen: Point) -> bool {
+        VPlotter::is_reachable(self, pen)
+    }
+}
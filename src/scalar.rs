@@ -0,0 +1,303 @@
+//! Number types points and curves can be computed in: `f64`, `f32` for MCUs with a
+//! single precision FPU and `Fixed` for MCUs without any FPU.
+//!
+//! Segment set-up (arc centers, step counts) is always done in `f64`, once per segment;
+//! the per-point curve evaluation runs in the chosen scalar.
+
+use crate::float::{cos, cosf, sin, sinf, sqrt, sqrtf};
+use core::fmt::Debug;
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+pub trait Scalar:
+    Copy
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// What forward differencing accumulates in; wider than `Self` where the small
+    /// higher differences would otherwise be lost to rounding.
+    type Difference: Copy + Add<Output = Self::Difference>;
+
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_f64(value: f64) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    /// `numerator / denominator` without a round trip through `f64`.
+    fn from_ratio(numerator: usize, denominator: usize) -> Self;
+
+    fn sqrt(self) -> Self;
+
+    fn sin(self) -> Self;
+
+    fn cos(self) -> Self;
+
+    fn difference_from_f64(value: f64) -> Self::Difference;
+
+    fn from_difference(difference: Self::Difference) -> Self;
+}
+
+impl Scalar for f64 {
+    type Difference = f64;
+
+    const ZERO: Self = 0.;
+    const ONE: Self = 1.;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_ratio(numerator: usize, denominator: usize) -> Self {
+        numerator as f64 / denominator as f64
+    }
+
+    fn sqrt(self) -> Self {
+        sqrt(self)
+    }
+
+    fn sin(self) -> Self {
+        sin(self)
+    }
+
+    fn cos(self) -> Self {
+        cos(self)
+    }
+
+    fn difference_from_f64(value: f64) -> Self::Difference {
+        value
+    }
+
+    fn from_difference(difference: Self::Difference) -> Self {
+        difference
+    }
+}
+
+impl Scalar for f32 {
+    type Difference = f32;
+
+    const ZERO: Self = 0.;
+    const ONE: Self = 1.;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_ratio(numerator: usize, denominator: usize) -> Self {
+        numerator as f32 / denominator as f32
+    }
+
+    fn sqrt(self) -> Self {
+        sqrtf(self)
+    }
+
+    fn sin(self) -> Self {
+        sinf(self)
+    }
+
+    fn cos(self) -> Self {
+        cosf(self)
+    }
+
+    fn difference_from_f64(value: f64) -> Self::Difference {
+        value as f32
+    }
+
+    fn from_difference(difference: Self::Difference) -> Self {
+        difference
+    }
+}
+
+/// Signed Q16.16 fixed-point number: 16 integer bits give a range of about ±32767
+/// and 16 fractional bits a resolution of about 0.000015.
+///
+/// Arithmetic saturates instead of overflowing, division by zero gives the largest
+/// value of the dividend's sign.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed(i32);
+
+const FRACTION_BITS: u32 = 16;
+const FIXED_ONE: i64 = 1 << FRACTION_BITS;
+const FIXED_PI: i64 = 205_887;
+const FIXED_HALF_PI: i64 = 102_944;
+const FIXED_TWO_PI: i64 = 411_775;
+const SERIES_BITS: u32 = 30;
+const SERIES_ONE: i64 = 1 << SERIES_BITS;
+const DIFFERENCE_BITS: u32 = 40;
+const DIFFERENCE_ONE: i64 = 1 << DIFFERENCE_BITS;
+
+impl Fixed {
+    pub const MAX: Fixed = Fixed(i32::MAX);
+    pub const MIN: Fixed = Fixed(i32::MIN);
+
+    pub const fn from_bits(bits: i32) -> Self {
+        Fixed(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    fn saturate(value: i64) -> Self {
+        if value > i32::MAX as i64 {
+            Fixed::MAX
+        } else if value < i32::MIN as i64 {
+            Fixed::MIN
+        } else {
+            Fixed(value as i32)
+        }
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: Fixed) -> Fixed {
+        let product = self.0 as i64 * rhs.0 as i64;
+        Fixed::saturate((product + (FIXED_ONE >> 1)) >> FRACTION_BITS)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    fn div(self, rhs: Fixed) -> Fixed {
+        if rhs.0 == 0 {
+            return if self.0 < 0 { Fixed::MIN } else { Fixed::MAX };
+        }
+        Fixed::saturate(((self.0 as i64) << FRACTION_BITS) / rhs.0 as i64)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(self.0.saturating_neg())
+    }
+}
+
+impl Scalar for Fixed {
+    /// Q24.40
+    type Difference = i64;
+
+    const ZERO: Self = Fixed(0);
+    const ONE: Self = Fixed(FIXED_ONE as i32);
+
+    fn from_f64(value: f64) -> Self {
+        // `as` saturates and maps NaN to zero
+        let scaled = value * FIXED_ONE as f64;
+        Fixed((scaled + if scaled < 0. { -0.5 } else { 0.5 }) as i32)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / FIXED_ONE as f64
+    }
+
+    fn from_ratio(numerator: usize, denominator: usize) -> Self {
+        if denominator == 0 {
+            return Fixed::MAX;
+        }
+        Fixed::saturate(((numerator as i64) << FRACTION_BITS) / denominator as i64)
+    }
+
+    fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Fixed::ZERO;
+        }
+        Fixed(integer_sqrt((self.0 as u64) << FRACTION_BITS) as i32)
+    }
+
+    fn sin(self) -> Self {
+        Fixed(fixed_sin(self.0 as i64) as i32)
+    }
+
+    fn cos(self) -> Self {
+        Fixed(fixed_sin(self.0 as i64 + FIXED_HALF_PI) as i32)
+    }
+
+    fn difference_from_f64(value: f64) -> Self::Difference {
+        // kept well inside i64 so that a few additions can not overflow
+        let limit = (1i64 << 62) as f64;
+        (value * DIFFERENCE_ONE as f64).max(-limit).min(limit) as i64
+    }
+
+    fn from_difference(difference: Self::Difference) -> Self {
+        let shift = DIFFERENCE_BITS - FRACTION_BITS;
+        Fixed::saturate((difference + (1 << (shift - 1))) >> shift)
+    }
+}
+
+fn integer_sqrt(value: u64) -> u64 {
+    let mut remainder = value;
+    let mut root = 0;
+    let mut bit = 1u64 << 62;
+    while bit > remainder {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+// sine of a Q16.16 angle, folded into [-pi/2, pi/2] and summed up to the x^9 term
+// with 30 fractional bits so that rounding stays below the Q16.16 resolution
+fn fixed_sin(angle: i64) -> i64 {
+    let mut x = angle % FIXED_TWO_PI;
+    if x > FIXED_PI {
+        x -= FIXED_TWO_PI;
+    } else if x < -FIXED_PI {
+        x += FIXED_TWO_PI;
+    }
+    if x > FIXED_HALF_PI {
+        x = FIXED_PI - x;
+    } else if x < -FIXED_HALF_PI {
+        x = -FIXED_PI - x;
+    }
+
+    let x = x << (SERIES_BITS - FRACTION_BITS);
+    let square = (x * x) >> SERIES_BITS;
+    let mut sum = SERIES_ONE;
+    for divisor in &[72, 42, 20, 6] {
+        sum = SERIES_ONE - ((square * sum) >> SERIES_BITS) / divisor;
+    }
+    let sine = (x * sum) >> SERIES_BITS;
+    (sine + (1 << (SERIES_BITS - FRACTION_BITS - 1))) >> (SERIES_BITS - FRACTION_BITS)
+}
@@ -0,0 +1,102 @@
+use super::math::{clamp_ticks, distance, CurvePoint};
+use super::point::Point;
+use super::tick_timer::TickTimer;
+use crate::float::ceil;
+use crate::scalar::Scalar;
+
+// how much finer than the requested spacing the curve is measured
+const FINE_STEPS_PER_SPACING: f64 = 8.;
+
+/// Yields curve times that are evenly spaced along the curve length. The spacing is
+/// shrunk a little so that a whole number of steps fits and the last time is exactly 1.0.
+pub struct ArcLengthTimer<S: Scalar = f64> {
+    fine_time: TickTimer,
+    spacing: S,
+    steps: usize,
+    emitted: usize,
+    travelled: S,
+    prev_time: S,
+    prev_point: Point<S>,
+    pending: Option<(S, Point<S>)>,
+}
+
+impl<S: Scalar> ArcLengthTimer<S> {
+    pub fn new(curve: &impl CurvePoint<S>, spacing: f64) -> Self {
+        let coarse_ticks = curve.ticks_for_tolerance(spacing / FINE_STEPS_PER_SPACING);
+        let coarse_length = curve_length(curve, coarse_ticks);
+        let fine_ticks = coarse_ticks.max(clamp_ticks(ceil(
+            coarse_length * FINE_STEPS_PER_SPACING / spacing,
+        )));
+
+        // measure again with the same steps used for walking, so the last step is not short
+        let length = curve_length(curve, fine_ticks);
+        let steps = clamp_ticks(ceil(length / spacing));
+
+        ArcLengthTimer {
+            fine_time: TickTimer::with_ticks(fine_ticks),
+            spacing: S::from_f64(length / steps as f64),
+            steps,
+            emitted: 0,
+            travelled: S::ZERO,
+            prev_time: S::ZERO,
+            prev_point: curve.at(S::ZERO),
+            pending: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.emitted >= self.steps
+    }
+
+    pub fn next_time(&mut self, curve: &impl CurvePoint<S>) -> Option<S> {
+        if self.is_finished() {
+            return None;
+        }
+        if self.emitted + 1 == self.steps {
+            self.emitted += 1;
+            return Some(S::ONE);
+        }
+
+        loop {
+            let (time, point) = match self.pending.take() {
+                Some(pending) => pending,
+                None => match self.fine_time.next_time() {
+                    Some(time) => (time, curve.at(time)),
+                    None => {
+                        self.emitted = self.steps;
+                        return Some(S::ONE);
+                    }
+                },
+            };
+
+            let step = distance(self.prev_point, point);
+            if step > S::ZERO && self.travelled + step >= self.spacing {
+                let ratio = (self.spacing - self.travelled) / step;
+                let emitted_time = self.prev_time + (time - self.prev_time) * ratio;
+                self.prev_time = emitted_time;
+                self.prev_point = curve.at(emitted_time);
+                self.travelled = S::ZERO;
+                self.pending = Some((time, point));
+                self.emitted += 1;
+                return Some(emitted_time);
+            }
+
+            self.travelled = self.travelled + step;
+            self.prev_time = time;
+            self.prev_point = point;
+        }
+    }
+}
+
+// summed in the curve's scalar, only the total is handed back as f64
+fn curve_length<S: Scalar>(curve: &impl CurvePoint<S>, ticks: usize) -> f64 {
+    let mut timer = TickTimer::with_ticks(ticks);
+    let mut prev_point = curve.at(S::ZERO);
+    let mut length = S::ZERO;
+    while let Some(time) = timer.next_time() {
+        let point = curve.at(time);
+        length = length + distance(prev_point, point);
+        prev_point = point;
+    }
+    length.to_f64()
+}
@@ -0,0 +1,373 @@
+use crate::float::{cos, sin, sqrt};
+#[cfg(feature = "svgtypes")]
+use svgtypes::PathSegment;
+
+use super::line_to::{CircularArc, LineTo};
+use super::math::*;
+use super::point::*;
+use super::primitive::*;
+
+/// Turns path segments into lines and circular arcs. Circular SVG arcs are passed
+/// through exactly, every other curve is approximated by biarcs that stay within
+/// `tolerance` (in output units) of it. Curves are halved at most 16 times to get
+/// there; biarcs of the last halves are kept even if they miss, and where no biarc
+/// joins the tangents at all, as at a cusp, a straight line is drawn.
+#[cfg(feature = "svgtypes")]
+pub fn arcs_from_path_segments(
+    path_segments: impl Iterator<Item = PathSegment>,
+    tolerance: f64,
+) -> impl Iterator<Item = LineTo> {
+    arcs_from_primitives(primitives_from_path_segments(path_segments), tolerance)
+}
+
+/// `arcs_from_path_segments` for primitives resolved elsewhere, without a path parser.
+pub fn arcs_from_primitives(
+    primitives: impl Iterator<Item = Primitive>,
+    tolerance: f64,
+) -> impl Iterator<Item = LineTo> {
+    let mut pen = None;
+    primitives
+        .filter(move |primitive| moves_the_pen(primitive, &mut pen))
+        .flat_map(move |primitive| calc_arc_iterator(primitive, tolerance))
+}
+
+// === private members ===
+
+// every curve is cut in two at most this many times; the biarcs of the shortest
+// intervals are taken without checking them against the tolerance
+const MAX_DEPTH: usize = 16;
+// points checked along each curve interval and along each piece of its biarc
+const ERROR_SAMPLES: usize = 16;
+
+enum ArcIterator {
+    Single(Option<LineTo>),
+    SquareCurve(BiarcFitter<SquareCurve>),
+    CubicCurve(BiarcFitter<CubicCurve>),
+    EllipseCurve(BiarcFitter<EllipseCurve>),
+}
+
+impl Iterator for ArcIterator {
+    type Item = LineTo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ArcIterator::Single(line_to) => line_to.take(),
+            ArcIterator::SquareCurve(fitter) => fitter.next(),
+            ArcIterator::CubicCurve(fitter) => fitter.next(),
+            ArcIterator::EllipseCurve(fitter) => fitter.next(),
+        }
+    }
+}
+
+fn calc_arc_iterator(primitive: Primitive, tolerance: f64) -> ArcIterator {
+    match primitive {
+        Primitive::Move { end } => ArcIterator::Single(Some(LineTo::Fly(end))),
+        Primitive::Line { start, end } if start == end => ArcIterator::Single(None),
+        Primitive::Line { end, .. } => ArcIterator::Single(Some(LineTo::Draw(end))),
+        Primitive::Quadratic { start, p1, end } => ArcIterator::SquareCurve(BiarcFitter::new(
+            SquareCurve::new(start, p1, end),
+            end,
+            tolerance,
+        )),
+        Primitive::Cubic { start, p1, p2, end } => ArcIterator::CubicCurve(BiarcFitter::new(
+            CubicCurve::new(start, p1, p2, end),
+            end,
+            tolerance,
+        )),
+        Primitive::Arc {
+            end,
+            center,
+            rx,
+            ry,
+            sweep_angle,
+            ..
+        } if rx == ry => ArcIterator::Single(Some(LineTo::Arc(CircularArc {
+            end,
+            center,
+            radius: rx,
+            clockwise: sweep_angle > 0.,
+        }))),
+        Primitive::Arc {
+            end,
+            center,
+            rx,
+            ry,
+            x_rotation,
+            start_angle,
+            sweep_angle,
+            ..
+        } => ArcIterator::EllipseCurve(BiarcFitter::new(
+            EllipseCurve::new(
+                start_angle,
+                sweep_angle,
+                rx,
+                ry,
+                x_rotation,
+                center.x,
+                center.y,
+            ),
+            end,
+            tolerance,
+        )),
+    }
+}
+
+/// Splits a curve into time intervals until a biarc fits each of them, depth first,
+/// so the arcs come out in drawing order.
+struct BiarcFitter<C: CurvePoint> {
+    curve: C,
+    end: Point,
+    tolerance: f64,
+    intervals: [(f64, f64, usize); MAX_DEPTH + 1],
+    intervals_len: usize,
+    pending: Option<LineTo>,
+}
+
+impl<C: CurvePoint> BiarcFitter<C> {
+    fn new(curve: C, end: Point, tolerance: f64) -> Self {
+        let mut intervals = [(0., 0., 0); MAX_DEPTH + 1];
+        intervals[0] = (0., 1., 0);
+        BiarcFitter {
+            curve,
+            end,
+            tolerance,
+            intervals,
+            intervals_len: 1,
+            pending: None,
+        }
+    }
+}
+
+impl<C: CurvePoint> Iterator for BiarcFitter<C> {
+    type Item = LineTo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(pending) = self.pending.take() {
+            return Some(pending);
+        }
+        while self.intervals_len > 0 {
+            self.intervals_len -= 1;
+            let (from, to, depth) = self.intervals[self.intervals_len];
+
+            let end = if to == 1. {
+                self.end
+            } else {
+                self.curve.at(to)
+            };
+            let checked = depth < MAX_DEPTH;
+            match fit_biarc(&self.curve, from, to, end, self.tolerance, checked) {
+                Some((first, second)) => {
+                    self.pending = second;
+                    return Some(first);
+                }
+                None if checked => {
+                    let middle = (from + to) / 2.;
+                    self.intervals[self.intervals_len] = (middle, to, depth + 1);
+                    self.intervals[self.intervals_len + 1] = (from, middle, depth + 1);
+                    self.intervals_len += 2;
+                }
+                // no biarc joins the tangents at all, as at a cusp
+                None => return Some(LineTo::Draw(end)),
+            }
+        }
+        None
+    }
+}
+
+/// One piece of a biarc: a straight line or a circular arc.
+#[derive(Copy, Clone)]
+enum Piece {
+    Line { start: Point, end: Point },
+    Arc { start: Point, arc: CircularArc },
+}
+
+impl Piece {
+    // arc starting at `start` heading along `direction` and passing through `end`;
+    // arcs bulging less than a hundredth of the tolerance are drawn as lines
+    fn from_tangent(start: Point, direction: Point, end: Point, tolerance: f64) -> Self {
+        let chord = end - start;
+        let normal = Point::new(-direction.y, direction.x);
+        let normal_part = normal.x * chord.x + normal.y * chord.y;
+        let chord_square = sqr(chord.x) + sqr(chord.y);
+
+        if normal_part == 0. || normal_part.abs() / 4. <= tolerance / 100. {
+            return Piece::Line { start, end };
+        }
+
+        let offset = chord_square / (2. * normal_part);
+        Piece::Arc {
+            start,
+            arc: CircularArc {
+                end,
+                center: start + normal * offset,
+                radius: offset.abs(),
+                clockwise: normal_part > 0.,
+            },
+        }
+    }
+
+    // same piece walked the other way; `start` is where it used to begin
+    fn reversed(self, start: Point) -> Self {
+        match self {
+            Piece::Line { start, end } => Piece::Line {
+                start: end,
+                end: start,
+            },
+            Piece::Arc { arc, .. } => Piece::Arc {
+                start: arc.end,
+                arc: CircularArc {
+                    end: start,
+                    clockwise: !arc.clockwise,
+                    ..arc
+                },
+            },
+        }
+    }
+
+    // the point `fraction` of the way along the piece
+    fn at(&self, fraction: f64) -> Point {
+        match self {
+            Piece::Line { start, end } => *start + (*end - *start) * fraction,
+            Piece::Arc { start, arc } => {
+                let (start_angle, sweep) = arc_angles(*start, arc);
+                let angle = start_angle + sweep * fraction;
+                arc.center + Point::new(cos(angle), sin(angle)) * arc.radius
+            }
+        }
+    }
+
+    // distance to the nearest point of the piece, not of the whole circle
+    fn distance_to(&self, point: Point) -> f64 {
+        match self {
+            Piece::Line { start, end } => distance_to_segment(*start, *end, point),
+            Piece::Arc { start, arc } => {
+                let (start_angle, sweep) = arc_angles(*start, arc);
+                // points level with the start are a whole turn away, but there the
+                // distance to `start` is the distance to the arc
+                let offset =
+                    arc_sweep(angle_around(arc.center, point) - start_angle, arc.clockwise);
+                if offset.abs() <= sweep.abs() {
+                    (distance(arc.center, point) - arc.radius).abs()
+                } else {
+                    distance(*start, point).min(distance(arc.end, point))
+                }
+            }
+        }
+    }
+
+    fn into_line_to(self) -> LineTo {
+        match self {
+            Piece::Line { end, .. } => LineTo::Draw(end),
+            Piece::Arc { arc, .. } => LineTo::Arc(arc),
+        }
+    }
+}
+
+// the biarc joining the curve's tangents at `from` and `to`, `None` if there is none
+// or, when `checked`, if it is further than the tolerance from the curve
+fn fit_biarc(
+    curve: &impl CurvePoint,
+    from: f64,
+    to: f64,
+    end: Point,
+    tolerance: f64,
+    checked: bool,
+) -> Option<(LineTo, Option<LineTo>)> {
+    let start = curve.at(from);
+    let start_tangent = unit_tangent(curve, from, to)?;
+    let end_tangent = unit_tangent(curve, to, from)?;
+
+    let chord = end - start;
+    let tangent_sum = start_tangent + end_tangent;
+    let chord_square = sqr(chord.x) + sqr(chord.y);
+    let chord_along_sum = chord.x * tangent_sum.x + chord.y * tangent_sum.y;
+    let denominator =
+        2. * (1. - (start_tangent.x * end_tangent.x + start_tangent.y * end_tangent.y));
+
+    let handle = if denominator.abs() < 1e-12 {
+        if chord_along_sum <= 0. {
+            return None;
+        }
+        chord_square / (2. * chord_along_sum)
+    } else {
+        (-chord_along_sum + sqrt(sqr(chord_along_sum) + denominator * chord_square)) / denominator
+    };
+    if !handle.is_finite() || handle <= 0. {
+        return None;
+    }
+
+    let joint = (start + start_tangent * handle + end - end_tangent * handle) / 2.;
+    let first = Piece::from_tangent(start, start_tangent, joint, tolerance);
+    let second = Piece::from_tangent(end, end_tangent * -1., joint, tolerance).reversed(end);
+
+    if checked && !within_tolerance(curve, from, to, end, [first, second], tolerance) {
+        return None;
+    }
+
+    if let (Piece::Line { .. }, Piece::Line { .. }) = (first, second) {
+        return Some((LineTo::Draw(end), None));
+    }
+    if distance(start, joint) == 0. {
+        return Some((second.into_line_to(), None));
+    }
+    if distance(joint, end) == 0. {
+        return Some((first.into_line_to(), None));
+    }
+    Some((first.into_line_to(), Some(second.into_line_to())))
+}
+
+// the curve has to be close to the biarc and the biarc close to the curve, which
+// is measured against a polyline through the curve samples
+fn within_tolerance(
+    curve: &impl CurvePoint,
+    from: f64,
+    to: f64,
+    end: Point,
+    [first, second]: [Piece; 2],
+    tolerance: f64,
+) -> bool {
+    let mut samples = [end; ERROR_SAMPLES + 1];
+    for (sample, point) in samples.iter_mut().enumerate().take(ERROR_SAMPLES) {
+        *point = curve.at(from + (to - from) * sample as f64 / ERROR_SAMPLES as f64);
+    }
+    let curve_near_biarc = samples
+        .iter()
+        .all(|point| first.distance_to(*point).min(second.distance_to(*point)) <= tolerance);
+    let biarc_near_curve = [first, second].iter().all(|piece| {
+        (1..ERROR_SAMPLES).all(|sample| {
+            let point = piece.at(sample as f64 / ERROR_SAMPLES as f64);
+            samples
+                .windows(2)
+                .any(|chord| distance_to_segment(chord[0], chord[1], point) <= tolerance)
+        })
+    });
+    curve_near_biarc && biarc_near_curve
+}
+
+// direction of the curve at `time`, looking towards `towards` when the derivative vanishes
+fn unit_tangent(curve: &impl CurvePoint, time: f64, towards: f64) -> Option<Point> {
+    let derivative = curve.derivative(time);
+    let derivative_length = length(derivative);
+    if derivative_length > 1e-12 {
+        return Some(derivative / derivative_length);
+    }
+
+    let step = curve.at(time + (towards - time) * 1e-3) - curve.at(time);
+    let step_length = length(step);
+    if step_length > 0. {
+        let direction = if towards > time { step } else { step * -1. };
+        Some(direction / step_length)
+    } else {
+        None
+    }
+}
+
+// start angle and signed sweep of the arc from `start`
+fn arc_angles(start: Point, arc: &CircularArc) -> (f64, f64) {
+    let start_angle = angle_around(arc.center, start);
+    let sweep = arc_sweep(
+        angle_around(arc.center, arc.end) - start_angle,
+        arc.clockwise,
+    );
+    (start_angle, sweep)
+}
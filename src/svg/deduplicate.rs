@@ -0,0 +1,192 @@
+//! Cutting out strokes that retrace lines drawn before, like the edge two neighbouring
+//! shapes share.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::line_to::LineTo;
+use super::math::{distance, distance_to_segment};
+use super::point::Point;
+use super::sub_path::{line_tos_from_sub_paths, sub_paths, PointGrid, SubPath};
+use crate::float::ceil;
+
+/// Removes every stretch of line that runs within `tolerance` of a stretch drawn
+/// earlier, so each one is drawn once, by whichever sub-path gets there first. Only
+/// lines that overlap lengthwise count; crossings are kept. Sub-paths are split where
+/// a piece is cut out, and leftovers of a cut no longer than `tolerance` are dropped.
+pub fn deduplicate_sub_paths(sub_paths: Vec<SubPath>, tolerance: f64) -> Vec<SubPath> {
+    let mut drawn = DrawnSegments::new(&sub_paths, tolerance);
+    let mut deduplicated = Vec::new();
+    for sub_path in sub_paths {
+        let mut points: Vec<Point> = Vec::new();
+        for pair in sub_path.points.windows(2) {
+            for (start, end) in drawn.add(pair[0], pair[1]) {
+                if points.last() != Some(&start) {
+                    finish_sub_path(&mut deduplicated, &mut points);
+                    points.push(start);
+                }
+                points.push(end);
+            }
+        }
+        finish_sub_path(&mut deduplicated, &mut points);
+    }
+    deduplicated
+}
+
+/// `deduplicate_sub_paths` for a flattened stream, cut up and put back together as
+/// described in `sub_path`.
+pub fn deduplicate_line_tos(
+    line_tos: impl IntoIterator<Item = LineTo>,
+    tolerance: f64,
+) -> Vec<LineTo> {
+    line_tos_from_sub_paths(&deduplicate_sub_paths(sub_paths(line_tos), tolerance))
+}
+
+// === private members ===
+
+fn finish_sub_path(sub_paths: &mut Vec<SubPath>, points: &mut Vec<Point>) {
+    if points.len() > 1 {
+        sub_paths.push(SubPath {
+            points: core::mem::take(points),
+        });
+    } else {
+        points.clear();
+    }
+}
+
+/// The pieces kept so far, bucketed by the grid cells they pass through.
+struct DrawnSegments {
+    tolerance: f64,
+    grid: PointGrid,
+    segments: Vec<(Point, Point)>,
+    cells: BTreeMap<(i64, i64), Vec<usize>>,
+}
+
+impl DrawnSegments {
+    // cells about as long as an average segment keep both the number of cells per
+    // segment and the number of segments per cell low
+    fn new(sub_paths: &[SubPath], tolerance: f64) -> Self {
+        let (total, count) = sub_paths
+            .iter()
+            .flat_map(|sub_path| sub_path.points.windows(2))
+            .fold((0., 0), |(total, count), pair| {
+                (total + distance(pair[0], pair[1]), count + 1)
+            });
+        let average = if count > 0 { total / count as f64 } else { 0. };
+        DrawnSegments {
+            tolerance,
+            grid: PointGrid::new(average.max(2. * tolerance)),
+            segments: Vec::new(),
+            cells: BTreeMap::new(),
+        }
+    }
+
+    // draws `start` to `end`, returning the pieces that were not drawn before
+    fn add(&mut self, start: Point, end: Point) -> Vec<(Point, Point)> {
+        let length = distance(start, end);
+        if length == 0. {
+            return Vec::new();
+        }
+
+        let mut covered: Vec<(f64, f64)> = self
+            .nearby(start, end)
+            .into_iter()
+            .filter_map(|index| self.overlap(start, end, self.segments[index]))
+            .collect();
+        if covered.is_empty() {
+            self.insert((start, end));
+            return vec![(start, end)];
+        }
+        covered.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        // exact at both ends, so that kept pieces still join their neighbours
+        let at = |time: f64| match time {
+            time if time <= 0. => start,
+            time if time >= 1. => end,
+            time => start + (end - start) * time,
+        };
+        let mut pieces = Vec::new();
+        let mut from = 0.;
+        for (covered_from, covered_to) in covered.into_iter().chain([(1., 1.)]) {
+            if (covered_from - from) * length > self.tolerance {
+                pieces.push((at(from), at(covered_from)));
+            }
+            from = f64::max(from, covered_to);
+        }
+
+        for piece in pieces.iter() {
+            self.insert(*piece);
+        }
+        pieces
+    }
+
+    // the part of `start` to `end`, as a range of 0 to 1 along it, that `other` runs
+    // within the tolerance of
+    fn overlap(&self, start: Point, end: Point, other: (Point, Point)) -> Option<(f64, f64)> {
+        let direction = end - start;
+        let length_squared = direction.x * direction.x + direction.y * direction.y;
+        let along = |point: Point| {
+            let offset = point - start;
+            (offset.x * direction.x + offset.y * direction.y) / length_squared
+        };
+
+        let (mut near, mut far) = other;
+        let (mut near_along, mut far_along) = (along(near), along(far));
+        if near_along > far_along {
+            core::mem::swap(&mut near, &mut far);
+            core::mem::swap(&mut near_along, &mut far_along);
+        }
+        let from = near_along.max(0.);
+        let to = far_along.min(1.);
+        if to <= from {
+            return None;
+        }
+
+        // where `other` is at both ends of the shared range; it is straight, so it is
+        // close all along if it is close there
+        let at = |time: f64| near + (far - near) * ((time - near_along) / (far_along - near_along));
+        let line_distance = |point: Point| distance_to_segment(start, end, point);
+        if line_distance(at(from)) <= self.tolerance && line_distance(at(to)) <= self.tolerance {
+            Some((from, to))
+        } else {
+            None
+        }
+    }
+
+    fn insert(&mut self, segment: (Point, Point)) {
+        let index = self.segments.len();
+        self.segments.push(segment);
+        for key in self.cells_along(segment.0, segment.1) {
+            self.cells.entry(key).or_default().push(index);
+        }
+    }
+
+    fn nearby(&self, start: Point, end: Point) -> Vec<usize> {
+        let mut nearby: Vec<usize> = self
+            .cells_along(start, end)
+            .iter()
+            .filter_map(|key| self.cells.get(key))
+            .flatten()
+            .copied()
+            .collect();
+        nearby.sort_unstable();
+        nearby.dedup();
+        nearby
+    }
+
+    // the cells the segment passes through and their neighbours, which covers
+    // everything within one cell, and so within the tolerance, of it
+    fn cells_along(&self, start: Point, end: Point) -> Vec<(i64, i64)> {
+        let steps = ceil(distance(start, end) / self.grid.cell).max(1.) as usize;
+        let mut keys = Vec::new();
+        for step in 0..=steps {
+            let point = start + (end - start) * (step as f64 / steps as f64);
+            keys.extend(self.grid.around(point));
+        }
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+}
@@ -0,0 +1,540 @@
+//! Signed distance to the outline of a filled shape, sampled on a grid, and the
+//! contour lines of constant distance. Used for the contour-parallel fills.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::fill::{crossing, edges, is_inside, Edge, FillRule};
+use super::math::{distance, distance_to_segment};
+use super::point::Point;
+use super::sub_path::{PointGrid, SubPath};
+use super::transform::Transform;
+use crate::float::{ceil, floor, sqrt};
+
+// contours are within a small fraction of a cell of the true offset, so a few cells
+// per line spacing are plenty
+const CELLS_PER_SPACING: f64 = 4.;
+// larger shapes get coarser cells instead of more memory
+const MAX_NODES: f64 = (1 << 20) as f64;
+// points closer than this many cells to the line through their neighbours are dropped
+const COLLINEAR_TOLERANCE: f64 = 1e-3;
+// empty cells around the shape, so that every contour is closed
+const BORDER_CELLS: f64 = 2.;
+// how far, in cells, either side of an edge is looked at to tell whether it is outline
+const SIDE_OFFSET: f64 = 1e-3;
+// edges whose directions differ less than this, relative to their lengths, are parallel
+const PARALLEL_TOLERANCE: f64 = 1e-12;
+
+pub(crate) struct DistanceField {
+    origin: Point,
+    cell: f64,
+    columns: usize,
+    rows: usize,
+    /// Positive inside the shape, negative outside.
+    values: Vec<f64>,
+}
+
+impl DistanceField {
+    /// `None` for sub-paths without any edges, or with a NaN or infinite coordinate.
+    pub(crate) fn new(sub_paths: &[SubPath], fill_rule: FillRule, spacing: f64) -> Option<Self> {
+        let edges = edges(sub_paths, &Transform::IDENTITY);
+        let finite = |point: Point| point.x.is_finite() && point.y.is_finite();
+        if !edges
+            .iter()
+            .all(|edge| finite(edge.start) && finite(edge.end))
+        {
+            return None;
+        }
+        let (min, max) = bounds(&edges)?;
+        let (width, height) = (max.x - min.x, max.y - min.y);
+
+        let mut cell = spacing / CELLS_PER_SPACING;
+        let nodes = |cell: f64| {
+            (width / cell + 2. * BORDER_CELLS + 2.) * (height / cell + 2. * BORDER_CELLS + 2.)
+        };
+        if nodes(cell) > MAX_NODES {
+            cell = sqrt(width * height / MAX_NODES).max(cell) * 1.1;
+            while nodes(cell) > MAX_NODES {
+                cell *= 1.1;
+            }
+        }
+
+        let origin = Point::new(min.x - BORDER_CELLS * cell, min.y - BORDER_CELLS * cell);
+        let columns = ceil(width / cell + 2. * BORDER_CELLS) as usize + 1;
+        let rows = ceil(height / cell + 2. * BORDER_CELLS) as usize + 1;
+        let mut field = DistanceField {
+            origin,
+            cell,
+            columns,
+            rows,
+            values: Vec::new(),
+        };
+
+        let bands = EdgeBands::new(&edges);
+        let nearest =
+            field.nearest_outline_points(&boundary_edges(&edges, &bands, fill_rule, cell));
+        let inside = field.inside_nodes(&edges, &bands, fill_rule);
+        field.values = (0..columns * rows)
+            .map(|index| {
+                let node = field.node(index % columns, index / columns);
+                let distance = nearest[index].map_or(f64::INFINITY, |point| distance(node, point));
+                if inside[index] {
+                    distance
+                } else {
+                    -distance
+                }
+            })
+            .collect();
+        Some(field)
+    }
+
+    /// The largest distance from the outline found inside the shape.
+    pub(crate) fn max_value(&self) -> f64 {
+        self.values
+            .iter()
+            .fold(f64::NEG_INFINITY, |max, value| max.max(*value))
+    }
+
+    /// Closed lines at distance `level` inside the outline. Each starts and ends on the
+    /// same point, and all run the same way round the region deeper inside.
+    pub(crate) fn contours(&self, level: f64) -> Vec<Vec<Point>> {
+        let mut points: BTreeMap<usize, Point> = BTreeMap::new();
+        let mut next_edge: BTreeMap<usize, usize> = BTreeMap::new();
+        for row in 0..self.rows - 1 {
+            for column in 0..self.columns - 1 {
+                self.cell_segments(column, row, level, &mut points, &mut next_edge);
+            }
+        }
+
+        let mut contours = Vec::new();
+        while let Some((&start, _)) = next_edge.iter().next() {
+            let mut contour = Vec::new();
+            let mut edge = start;
+            while let Some(next) = next_edge.remove(&edge) {
+                contour.push(points[&edge]);
+                edge = next;
+            }
+            let mut contour = self.without_collinear_points(contour);
+            if contour.len() > 2 {
+                contour.push(contour[0]);
+                contours.push(contour);
+            }
+        }
+        contours
+    }
+
+    // straight stretches of the outline give a point in every cell they cross
+    fn without_collinear_points(&self, contour: Vec<Point>) -> Vec<Point> {
+        let tolerance = self.cell * COLLINEAR_TOLERANCE;
+        let mut kept: Vec<Point> = Vec::with_capacity(contour.len());
+        for (index, point) in contour.iter().enumerate() {
+            let next = contour[(index + 1) % contour.len()];
+            match kept.last() {
+                Some(last) if distance_to_segment(*last, next, *point) < tolerance => {}
+                _ => kept.push(*point),
+            }
+        }
+        kept
+    }
+
+    fn node(&self, column: usize, row: usize) -> Point {
+        Point::new(
+            self.origin.x + column as f64 * self.cell,
+            self.origin.y + row as f64 * self.cell,
+        )
+    }
+
+    // nearest point of the outline for every node: exact next to the outline, then
+    // handed on from neighbour to neighbour in two sweeps over the grid
+    fn nearest_outline_points(&self, edges: &[Edge]) -> Vec<Option<Point>> {
+        let mut nearest: Vec<Option<Point>> = vec![None; self.columns * self.rows];
+        let offer = |nearest: &mut Vec<Option<Point>>, column: usize, row: usize, point: Point| {
+            let index = row * self.columns + column;
+            let node = self.node(column, row);
+            match nearest[index] {
+                Some(current) if distance(node, current) <= distance(node, point) => {}
+                _ => nearest[index] = Some(point),
+            }
+        };
+
+        for edge in edges {
+            let steps = ceil(distance(edge.start, edge.end) / self.cell).max(1.) as usize;
+            for step in 0..=steps {
+                let point = edge.start + (edge.end - edge.start) * (step as f64 / steps as f64);
+                let column = ((point.x - self.origin.x) / self.cell + 0.5) as usize;
+                let row = ((point.y - self.origin.y) / self.cell + 0.5) as usize;
+                for row in row - 1..=row + 1 {
+                    for column in column - 1..=column + 1 {
+                        let node = self.node(column, row);
+                        offer(&mut nearest, column, row, closest_on_edge(edge, node));
+                    }
+                }
+            }
+        }
+
+        let (columns, rows) = (self.columns as isize, self.rows as isize);
+        let pull = |nearest: &mut Vec<Option<Point>>,
+                    column: isize,
+                    row: isize,
+                    from: &[(isize, isize)]| {
+            for (d_column, d_row) in from {
+                let (other_column, other_row) = (column + d_column, row + d_row);
+                if other_column < 0 || other_column >= columns || other_row < 0 || other_row >= rows
+                {
+                    continue;
+                }
+                if let Some(point) = nearest[(other_row * columns + other_column) as usize] {
+                    offer(nearest, column as usize, row as usize, point);
+                }
+            }
+        };
+        for row in 0..rows {
+            for column in 0..columns {
+                pull(
+                    &mut nearest,
+                    column,
+                    row,
+                    &[(-1, -1), (0, -1), (1, -1), (-1, 0)],
+                );
+            }
+            for column in (0..columns).rev() {
+                pull(&mut nearest, column, row, &[(1, 0)]);
+            }
+        }
+        for row in (0..rows).rev() {
+            for column in (0..columns).rev() {
+                pull(
+                    &mut nearest,
+                    column,
+                    row,
+                    &[(1, 1), (0, 1), (-1, 1), (1, 0)],
+                );
+            }
+            for column in 0..columns {
+                pull(&mut nearest, column, row, &[(-1, 0)]);
+            }
+        }
+        nearest
+    }
+
+    fn inside_nodes(&self, edges: &[Edge], bands: &EdgeBands, fill_rule: FillRule) -> Vec<bool> {
+        let mut inside = Vec::with_capacity(self.columns * self.rows);
+        let mut crossings: Vec<(f64, i32)> = Vec::new();
+        for row in 0..self.rows {
+            let y = self.node(0, row).y;
+            crossings.clear();
+            crossings.extend(bands.crossings(edges, y));
+            crossings.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+            let mut winding = 0;
+            let mut next_crossing = 0;
+            for column in 0..self.columns {
+                let x = self.node(column, row).x;
+                while next_crossing < crossings.len() && crossings[next_crossing].0 < x {
+                    winding += crossings[next_crossing].1;
+                    next_crossing += 1;
+                }
+                inside.push(is_inside(winding, fill_rule));
+            }
+        }
+        inside
+    }
+
+    // marching squares for one cell. Corners and edges are numbered clockwise on screen
+    // from the top left; a segment runs from where the contour leaves the deeper region
+    // to where it enters it, which keeps the deeper side on the same hand everywhere.
+    fn cell_segments(
+        &self,
+        column: usize,
+        row: usize,
+        level: f64,
+        points: &mut BTreeMap<usize, Point>,
+        next_edge: &mut BTreeMap<usize, usize>,
+    ) {
+        let corners = [
+            (column, row),
+            (column + 1, row),
+            (column + 1, row + 1),
+            (column, row + 1),
+        ];
+        let values = corners.map(|(column, row)| self.values[row * self.columns + column]);
+        let above = values.map(|value| value > level);
+        if above.iter().all(|above| *above) || !above.iter().any(|above| *above) {
+            return;
+        }
+
+        let horizontal = |column: usize, row: usize| 2 * (row * self.columns + column);
+        let vertical = |column: usize, row: usize| 2 * (row * self.columns + column) + 1;
+        let edge_ids = [
+            horizontal(column, row),
+            vertical(column + 1, row),
+            horizontal(column, row + 1),
+            vertical(column, row),
+        ];
+
+        // (edge, leaves the deeper region) in clockwise order
+        let mut crossings = [(0, false); 4];
+        let mut count = 0;
+        for edge in 0..4 {
+            let next = (edge + 1) % 4;
+            if above[edge] != above[next] {
+                let (from, to) = (corners[edge], corners[next]);
+                let ratio = (level - values[edge]) / (values[next] - values[edge]);
+                let from = self.node(from.0, from.1);
+                let to = self.node(to.0, to.1);
+                points
+                    .entry(edge_ids[edge])
+                    .or_insert(from + (to - from) * ratio);
+                crossings[count] = (edge_ids[edge], above[edge]);
+                count += 1;
+            }
+        }
+
+        // a saddle joins the deeper corners when the cell centre is deep as well
+        let centre_above = values.iter().sum::<f64>() / 4. > level;
+        for index in 0..count {
+            let (edge, leaves) = crossings[index];
+            if leaves {
+                let entry = if count == 2 || centre_above {
+                    (index + 1) % count
+                } else {
+                    (index + count - 1) % count
+                };
+                next_edge.insert(edge, crossings[entry].0);
+            }
+        }
+    }
+}
+
+// === private members ===
+
+fn bounds(edges: &[Edge]) -> Option<(Point, Point)> {
+    edges.iter().fold(None, |bounds, edge| {
+        let (low, high) = match bounds {
+            None => (edge.start, edge.start),
+            Some(bounds) => bounds,
+        };
+        Some((
+            Point::new(
+                low.x.min(edge.start.x).min(edge.end.x),
+                low.y.min(edge.start.y).min(edge.end.y),
+            ),
+            Point::new(
+                high.x.max(edge.start.x).max(edge.end.x),
+                high.y.max(edge.start.y).max(edge.end.y),
+            ),
+        ))
+    })
+}
+
+// the pieces of the edges with the inside on one side only. Edges are cut where they
+// cross or overlap others, and each piece is tested just off its middle, so sub-paths
+// that the fill rule merges into one region do not count as outline.
+fn boundary_edges(edges: &[Edge], bands: &EdgeBands, fill_rule: FillRule, cell: f64) -> Vec<Edge> {
+    let offset = cell * SIDE_OFFSET;
+    let grid = EdgeGrid::new(edges, 2. * offset);
+    let mut boundary = Vec::new();
+    let mut cuts: Vec<f64> = Vec::new();
+    for edge in edges {
+        cuts.clear();
+        cuts.extend([0., 1.]);
+        for other in grid.near(edge) {
+            cut_positions(edge, &edges[other], offset, &mut cuts);
+        }
+        cuts.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let direction = edge.end - edge.start;
+        let length = distance(edge.start, edge.end);
+        let normal = Point::new(-direction.y, direction.x) * (offset / length);
+        for pair in cuts.windows(2) {
+            if (pair[1] - pair[0]) * length < offset {
+                continue;
+            }
+            let middle = edge.start + direction * ((pair[0] + pair[1]) / 2.);
+            let left = is_inside(bands.winding(edges, middle + normal), fill_rule);
+            let right = is_inside(bands.winding(edges, middle - normal), fill_rule);
+            if left != right {
+                boundary.push(Edge {
+                    start: edge.start + direction * pair[0],
+                    end: edge.start + direction * pair[1],
+                });
+            }
+        }
+    }
+    boundary
+}
+
+// where along `edge` (from 0 to 1) `other` crosses it or, lying on the same line
+// within `tolerance`, starts or ends
+fn cut_positions(edge: &Edge, other: &Edge, tolerance: f64, cuts: &mut Vec<f64>) {
+    let cross = |a: Point, b: Point| a.x * b.y - a.y * b.x;
+    let direction = edge.end - edge.start;
+    let other_direction = other.end - other.start;
+    let to_other = other.start - edge.start;
+    let denominator = cross(direction, other_direction);
+    let scale = distance(edge.start, edge.end) * distance(other.start, other.end);
+    if denominator.abs() > PARALLEL_TOLERANCE * scale {
+        let along_edge = cross(to_other, other_direction) / denominator;
+        let along_other = cross(to_other, direction) / denominator;
+        if (0.0..=1.).contains(&along_edge) && (0.0..=1.).contains(&along_other) {
+            cuts.push(along_edge);
+        }
+    } else if cross(to_other, direction).abs() <= tolerance * distance(edge.start, edge.end) {
+        let length_squared = direction.x * direction.x + direction.y * direction.y;
+        for point in [other.start, other.end] {
+            let offset = point - edge.start;
+            let along_edge = (offset.x * direction.x + offset.y * direction.y) / length_squared;
+            if (0.0..=1.).contains(&along_edge) {
+                cuts.push(along_edge);
+            }
+        }
+    }
+}
+
+/// Edges bucketed by the cells they pass through, so that those crossing or touching an
+/// edge are found without looking at all of them.
+struct EdgeGrid {
+    grid: PointGrid,
+    cells: BTreeMap<(i64, i64), Vec<usize>>,
+}
+
+impl EdgeGrid {
+    // cells are about as big as an edge is long, and at least `min_cell`
+    fn new(edges: &[Edge], min_cell: f64) -> Self {
+        let total: f64 = edges
+            .iter()
+            .map(|edge| distance(edge.start, edge.end))
+            .sum();
+        let average = if edges.is_empty() {
+            0.
+        } else {
+            total / edges.len() as f64
+        };
+        let mut grid = EdgeGrid {
+            grid: PointGrid::new(average.max(min_cell)),
+            cells: BTreeMap::new(),
+        };
+        for (index, edge) in edges.iter().enumerate() {
+            for point in grid.points_along(edge) {
+                let cell = grid.cells.entry(grid.grid.key(point)).or_default();
+                if cell.last() != Some(&index) {
+                    cell.push(index);
+                }
+            }
+        }
+        grid
+    }
+
+    // every edge within half a cell of `edge`, and some further away
+    fn near(&self, edge: &Edge) -> Vec<usize> {
+        let mut keys: Vec<(i64, i64)> = self
+            .points_along(edge)
+            .flat_map(|point| self.grid.around(point))
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        let mut near: Vec<usize> = keys
+            .iter()
+            .filter_map(|key| self.cells.get(key))
+            .flatten()
+            .copied()
+            .collect();
+        near.sort_unstable();
+        near.dedup();
+        near
+    }
+
+    // points along the edge at most half a cell apart. Two edges within half a cell of
+    // each other then have points at most a cell apart, in neighbouring cells.
+    fn points_along<'a>(&self, edge: &'a Edge) -> impl Iterator<Item = Point> + 'a {
+        let steps = ceil(2. * distance(edge.start, edge.end) / self.grid.cell).max(1.) as usize;
+        (0..=steps)
+            .map(move |step| edge.start + (edge.end - edge.start) * (step as f64 / steps as f64))
+    }
+}
+
+/// Edges bucketed by the horizontal bands they span, so that a horizontal line only
+/// has to be checked against the edges of one band.
+struct EdgeBands {
+    top: f64,
+    height: f64,
+    bands: Vec<Vec<usize>>,
+}
+
+impl EdgeBands {
+    // bands are about as high as an edge, and there are no more bands than edges
+    fn new(edges: &[Edge]) -> Self {
+        let (top, bottom) = match bounds(edges) {
+            Some((min, max)) => (min.y, max.y),
+            None => (0., 0.),
+        };
+        let total: f64 = edges
+            .iter()
+            .map(|edge| (edge.end.y - edge.start.y).abs())
+            .sum();
+        let count = edges.len().max(1) as f64;
+        let height = (total / count).max((bottom - top) / count);
+        let band_count = if height > 0. {
+            floor((bottom - top) / height) as usize + 1
+        } else {
+            1
+        };
+        let mut bands = EdgeBands {
+            top,
+            height,
+            bands: vec![Vec::new(); band_count],
+        };
+        for (index, edge) in edges.iter().enumerate() {
+            let low = bands.band(edge.start.y.min(edge.end.y));
+            let high = bands.band(edge.start.y.max(edge.end.y));
+            if let (Some(low), Some(high)) = (low, high) {
+                for band in &mut bands.bands[low..=high] {
+                    band.push(index);
+                }
+            }
+        }
+        bands
+    }
+
+    fn band(&self, y: f64) -> Option<usize> {
+        let band = if self.height > 0. {
+            (y - self.top) / self.height
+        } else {
+            0.
+        };
+        // the bottom of the last band belongs to it
+        if band >= 0. && band <= self.bands.len() as f64 {
+            Some((band as usize).min(self.bands.len() - 1))
+        } else {
+            None
+        }
+    }
+
+    // where the horizontal line at `y` crosses the edges, as `fill::crossing` gives them
+    fn crossings<'a>(&'a self, edges: &'a [Edge], y: f64) -> impl Iterator<Item = (f64, i32)> + 'a {
+        let band: &[usize] = match self.band(y) {
+            Some(band) => &self.bands[band],
+            None => &[],
+        };
+        band.iter()
+            .filter_map(move |index| crossing(&edges[*index], y))
+    }
+
+    // winding number of the outline around `point`, counted as `inside_nodes` does
+    fn winding(&self, edges: &[Edge], point: Point) -> i32 {
+        self.crossings(edges, point.y)
+            .filter(|(x, _)| *x < point.x)
+            .map(|(_, direction)| direction)
+            .sum()
+    }
+}
+
+fn closest_on_edge(edge: &Edge, point: Point) -> Point {
+    let direction = edge.end - edge.start;
+    let length_squared = direction.x * direction.x + direction.y * direction.y;
+    let offset = point - edge.start;
+    let ratio = ((offset.x * direction.x + offset.y * direction.y) / length_squared).clamp(0., 1.);
+    edge.start + direction * ratio
+}
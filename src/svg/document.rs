@@ -0,0 +1,555 @@
+//! Reading whole SVG documents, as exported by Inkscape and friends, instead of
+//! single path data strings.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use svgtypes::{AspectRatio, Length, Paint, PathParser, PathSegment, ViewBox};
+
+use super::fill::FillRule;
+use super::flatten_options::FlattenOptions;
+use super::placement::*;
+use super::point::Point;
+use super::shapes::*;
+use super::svg_curve::{points_from_path_segments_with_options_as, LineTo};
+use super::transform::Transform;
+use crate::scalar::Scalar;
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+/// Why a document could not be read at all. Broken path data is not an error: the
+/// path is drawn up to the first bad segment.
+#[derive(Debug)]
+pub enum DocumentError {
+    Xml(roxmltree::Error),
+    /// The root element is not `<svg>`.
+    NotSvg,
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentError::Xml(error) => write!(f, "invalid XML: {}", error),
+            DocumentError::NotSvg => write!(f, "root element is not <svg>"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DocumentError {}
+
+impl From<roxmltree::Error> for DocumentError {
+    fn from(error: roxmltree::Error) -> Self {
+        DocumentError::Xml(error)
+    }
+}
+
+/// One drawable element of a document with a visible stroke or fill, in document order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawablePath {
+    pub segments: Vec<PathSegment>,
+    /// The element's `transform` composed with those of all its ancestors.
+    pub transform: Transform,
+    /// Whether the outline is drawn.
+    pub stroke: bool,
+    /// The `fill-rule` of an element whose inside is painted, for `fill::hatch`,
+    /// `fill::concentric` and `fill::spiral` after flattening it into sub-paths.
+    pub fill: Option<FillRule>,
+}
+
+/// Collects every path and basic shape of the document that would leave a visible
+/// stroke or fill, with shapes converted to path segments.
+///
+/// A stroke is visible unless it is `none` (the SVG default), has zero width or zero
+/// opacity. A fill is visible unless it is `none` or has zero opacity; it is black by
+/// default, and `<line>` elements have nothing to fill. Elements are skipped when
+/// neither is visible, when they or a parent have `display: none`,
+/// `visibility: hidden` or `opacity: 0`, and when they only serve as templates
+/// (`<defs>`, `<symbol>`, `<clipPath>`, `<mask>`, `<marker>`, `<pattern>`). Both
+/// presentation attributes and the `style` attribute are honoured.
+///
+/// A `<use>` draws what its `href` (or `xlink:href`) points to by id, moved by its `x`
+/// and `y` and styled as if it were the element's parent. A `<symbol>` it points to is
+/// drawn like a `<g>`, without its own `viewBox`. References to missing elements and
+/// references that lead back to themselves draw nothing.
+///
+/// Coordinates are in the root element's user units; see `Page` for millimetres.
+pub fn paths_from_document(text: &str) -> Result<Vec<DrawablePath>, DocumentError> {
+    let document = parse(text)?;
+    Ok(collect_document_paths(document.root_element()))
+}
+
+/// Flattens the outline of every element with a visible stroke, see
+/// `paths_from_document`.
+/// `options.transform` is applied after the document's own transforms.
+pub fn points_from_document(
+    text: &str,
+    options: FlattenOptions,
+) -> Result<Vec<LineTo>, DocumentError> {
+    points_from_document_as(text, options)
+}
+
+/// `points_from_document` with points computed in `S`, see `svg_curve`.
+pub fn points_from_document_as<S: Scalar>(
+    text: &str,
+    options: FlattenOptions,
+) -> Result<Vec<LineTo<S>>, DocumentError> {
+    Ok(flatten_paths(
+        paths_from_document(text)?,
+        Transform::IDENTITY,
+        options,
+    ))
+}
+
+/// Physical size of a document, from the root `width` and `height`, and where its
+/// user units end up on it, from `viewBox` and `preserveAspectRatio`.
+///
+/// A missing size is taken from the `viewBox` (one user unit per px) or, without one,
+/// is the 300 x 150 px browsers use. Percentages count as missing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Page {
+    /// Millimetres.
+    pub width: f64,
+    /// Millimetres.
+    pub height: f64,
+    /// From user units to millimetres on the page.
+    pub transform: Transform,
+}
+
+pub fn page_from_document(text: &str) -> Result<Page, DocumentError> {
+    let document = parse(text)?;
+    Ok(page(document.root_element()))
+}
+
+/// Flattens the document in board millimetres, with its page fitted into `placement`
+/// and then moved by `options.transform`. Tolerances and spacings in `options` are
+/// therefore board millimetres too, measured after both. Everything outside the page,
+/// as with `slice`, or outside `placement.area()` is cut off, see `clip_line_tos`.
+pub fn placed_points_from_document(
+    text: &str,
+    placement: &Placement,
+    options: FlattenOptions,
+) -> Result<Vec<LineTo>, DocumentError> {
+    let document = parse(text)?;
+    let root = document.root_element();
+    let page = page(root);
+    let page_rect = Rect::new(0., 0., page.width, page.height);
+    let page_to_board = placement.transform(page_rect);
+    let corner = page_to_board.apply(Point::ZERO);
+    let size = page_to_board.apply(Point::new(page.width, page.height)) - corner;
+    let page_on_board = Rect::new(corner.x, corner.y, size.x, size.y);
+
+    let points = flatten_paths(
+        collect_document_paths(root),
+        page.transform.then(&page_to_board),
+        options,
+    );
+    // `options.transform` may turn the page into a parallelogram, so the page is cut
+    // off where it was before that. Flattened paths only have `Fly` and `Draw` moves,
+    // which go back and forth through the transform exactly.
+    let on_page = match options.transform.inverse() {
+        Some(inverse) => clip_line_tos(
+            points
+                .into_iter()
+                .map(|line_to| transform_line_to(line_to, &inverse)),
+            page_on_board,
+        )
+        .map(|line_to| transform_line_to(line_to, &options.transform))
+        .collect(),
+        // squashed onto a line the page has no inside, only the area is left to cut to
+        None => points,
+    };
+    Ok(clip_line_tos(on_page.into_iter(), placement.area()).collect())
+}
+
+/// `placed_points_from_document` with the points converted to `S` at the end.
+pub fn placed_points_from_document_as<S: Scalar>(
+    text: &str,
+    placement: &Placement,
+    options: FlattenOptions,
+) -> Result<Vec<LineTo<S>>, DocumentError> {
+    let points = placed_points_from_document(text, placement, options)?;
+    Ok(points.into_iter().map(LineTo::cast).collect())
+}
+
+// === private members ===
+
+fn parse(text: &str) -> Result<roxmltree::Document<'_>, DocumentError> {
+    let document = roxmltree::Document::parse(text)?;
+    if !is_svg_element(document.root_element(), "svg") {
+        return Err(DocumentError::NotSvg);
+    }
+    Ok(document)
+}
+
+fn collect_document_paths(root: roxmltree::Node) -> Vec<DrawablePath> {
+    let mut paths = Vec::new();
+    collect_paths(
+        root,
+        Style::default(),
+        Transform::IDENTITY,
+        &mut Vec::new(),
+        &mut paths,
+    );
+    paths
+}
+
+// `to_output` goes between the document's transforms and `options.transform`
+fn flatten_paths<S: Scalar>(
+    paths: Vec<DrawablePath>,
+    to_output: Transform,
+    options: FlattenOptions,
+) -> Vec<LineTo<S>> {
+    let mut points = Vec::new();
+    for path in paths.into_iter().filter(|path| path.stroke) {
+        let path_options = FlattenOptions {
+            transform: path.transform.then(&to_output).then(&options.transform),
+            ..options
+        };
+        points.extend(points_from_path_segments_with_options_as(
+            path.segments.into_iter(),
+            path_options,
+        ));
+    }
+    points
+}
+
+fn page(root: roxmltree::Node) -> Page {
+    let view_box = view_box(root);
+    let width = length_attribute(root, "width").map(|px| px * MM_PER_PX);
+    let height = length_attribute(root, "height").map(|px| px * MM_PER_PX);
+    let (width, height) = match (width, height, view_box) {
+        (Some(width), Some(height), _) => (width, height),
+        (Some(width), None, Some(view_box)) => (width, width * view_box.h / view_box.w),
+        (None, Some(height), Some(view_box)) => (height * view_box.w / view_box.h, height),
+        (None, None, Some(view_box)) => (view_box.w * MM_PER_PX, view_box.h * MM_PER_PX),
+        (width, height, None) => (
+            width.unwrap_or(300. * MM_PER_PX),
+            height.unwrap_or(150. * MM_PER_PX),
+        ),
+    };
+
+    let transform = match view_box {
+        Some(view_box) => view_box_transform(
+            view_box,
+            aspect_ratio(root),
+            Rect::new(0., 0., width, height),
+        ),
+        None => Transform::scale(MM_PER_PX, MM_PER_PX),
+    };
+    Page {
+        width,
+        height,
+        transform,
+    }
+}
+
+// a nested `<svg>` is a viewport of its own, placed in its parent's user units
+fn nested_viewport_transform(node: roxmltree::Node) -> Transform {
+    let x = length_attribute(node, "x").unwrap_or(0.);
+    let y = length_attribute(node, "y").unwrap_or(0.);
+    match view_box(node) {
+        Some(view_box) => {
+            let width = length_attribute(node, "width").unwrap_or(view_box.w);
+            let height = length_attribute(node, "height").unwrap_or(view_box.h);
+            view_box_transform(view_box, aspect_ratio(node), Rect::new(x, y, width, height))
+        }
+        None => Transform::translate(x, y),
+    }
+}
+
+fn view_box(node: roxmltree::Node) -> Option<ViewBox> {
+    node.attribute("viewBox")
+        .and_then(|value| ViewBox::from_str(value).ok())
+        .filter(|view_box| view_box.w > 0. && view_box.h > 0.)
+}
+
+fn aspect_ratio(node: roxmltree::Node) -> AspectRatio {
+    node.attribute("preserveAspectRatio")
+        .and_then(|value| AspectRatio::from_str(value).ok())
+        .unwrap_or_default()
+}
+
+/// Stroke and fill related properties, as inherited down the element tree.
+#[derive(Debug, Copy, Clone)]
+struct Style {
+    stroke: bool,
+    stroke_width: f64,
+    stroke_opacity: f64,
+    fill: bool,
+    fill_opacity: f64,
+    fill_rule: FillRule,
+    visible: bool,
+    // not inherited, but a parent's opacity applies to everything inside it
+    opacity: f64,
+    display: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            stroke: false,
+            stroke_width: 1.,
+            stroke_opacity: 1.,
+            fill: true,
+            fill_opacity: 1.,
+            fill_rule: FillRule::NonZero,
+            visible: true,
+            opacity: 1.,
+            display: true,
+        }
+    }
+}
+
+impl Style {
+    fn of_element(node: roxmltree::Node, parent: Style) -> Self {
+        let mut style = Style {
+            opacity: 1.,
+            display: true,
+            ..parent
+        };
+        for attribute in node.attributes() {
+            if attribute.namespace().is_none() {
+                style.apply(attribute.name(), attribute.value());
+            }
+        }
+        // the style attribute overrides presentation attributes
+        if let Some(declarations) = node.attribute("style") {
+            for declaration in declarations.split(';') {
+                if let Some((name, value)) = declaration.split_once(':') {
+                    style.apply(name.trim(), value.trim());
+                }
+            }
+        }
+        style.opacity *= parent.opacity;
+        style
+    }
+
+    // values that are invalid or `inherit` keep what the parent had
+    fn apply(&mut self, name: &str, value: &str) {
+        match name {
+            "stroke" => match Paint::from_str(value) {
+                Ok(Paint::None) => self.stroke = false,
+                Ok(Paint::Inherit) | Err(_) => {}
+                Ok(_) => self.stroke = true,
+            },
+            "stroke-width" => {
+                if let Ok(width) = Length::from_str(value) {
+                    if width.num >= 0. {
+                        self.stroke_width = width.num;
+                    }
+                }
+            }
+            "stroke-opacity" => {
+                if let Some(opacity) = parse_opacity(value) {
+                    self.stroke_opacity = opacity;
+                }
+            }
+            "fill" => match Paint::from_str(value) {
+                Ok(Paint::None) => self.fill = false,
+                Ok(Paint::Inherit) | Err(_) => {}
+                Ok(_) => self.fill = true,
+            },
+            "fill-opacity" => {
+                if let Some(opacity) = parse_opacity(value) {
+                    self.fill_opacity = opacity;
+                }
+            }
+            "fill-rule" => match value {
+                "nonzero" => self.fill_rule = FillRule::NonZero,
+                "evenodd" => self.fill_rule = FillRule::EvenOdd,
+                _ => {}
+            },
+            "opacity" => {
+                if let Some(opacity) = parse_opacity(value) {
+                    self.opacity = opacity;
+                }
+            }
+            "visibility" => match value {
+                "visible" => self.visible = true,
+                "hidden" | "collapse" => self.visible = false,
+                _ => {}
+            },
+            "display" => self.display = value != "none",
+            _ => {}
+        }
+    }
+
+    fn is_stroke_visible(&self) -> bool {
+        self.stroke
+            && self.visible
+            && self.stroke_width > 0.
+            && self.stroke_opacity > 0.
+            && self.opacity > 0.
+    }
+
+    fn visible_fill(&self) -> Option<FillRule> {
+        let visible = self.fill && self.visible && self.fill_opacity > 0. && self.opacity > 0.;
+        Some(self.fill_rule).filter(|_| visible)
+    }
+}
+
+fn parse_opacity(value: &str) -> Option<f64> {
+    let opacity = match value.strip_suffix('%') {
+        Some(percent) => f64::from_str(percent.trim()).ok()? / 100.,
+        None => f64::from_str(value).ok()?,
+    };
+    Some(opacity.clamp(0., 1.))
+}
+
+fn is_svg_element(node: roxmltree::Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name && in_svg_namespace(node)
+}
+
+// foreign elements such as Inkscape's `sodipodi:namedview` are never drawn
+fn in_svg_namespace(node: roxmltree::Node) -> bool {
+    !matches!(node.tag_name().namespace(), Some(namespace) if namespace != SVG_NAMESPACE)
+}
+
+// `uses` are the `<use>` elements being expanded, to stop at reference cycles
+fn collect_paths(
+    node: roxmltree::Node,
+    parent: Style,
+    parent_transform: Transform,
+    uses: &mut Vec<roxmltree::NodeId>,
+    paths: &mut Vec<DrawablePath>,
+) {
+    let style = Style::of_element(node, parent);
+    if !style.display || style.opacity <= 0. {
+        return;
+    }
+    let mut transform = element_transform(node).then(&parent_transform);
+    if is_svg_element(node, "svg") && node.parent_element().is_some() {
+        transform = nested_viewport_transform(node).then(&transform);
+    }
+
+    for child in node
+        .children()
+        .filter(|child| child.is_element() && in_svg_namespace(*child))
+    {
+        collect_element(child, style, transform, uses, paths);
+    }
+}
+
+fn collect_element(
+    node: roxmltree::Node,
+    parent: Style,
+    parent_transform: Transform,
+    uses: &mut Vec<roxmltree::NodeId>,
+    paths: &mut Vec<DrawablePath>,
+) {
+    match node.tag_name().name() {
+        "g" | "svg" | "a" | "switch" => collect_paths(node, parent, parent_transform, uses, paths),
+        "path" | "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon" => {
+            let style = Style::of_element(node, parent);
+            let stroke = style.is_stroke_visible();
+            let fill = match node.tag_name().name() {
+                "line" => None,
+                _ => style.visible_fill(),
+            };
+            if style.display && (stroke || fill.is_some()) {
+                let segments = shape_segments(node);
+                if !segments.is_empty() {
+                    paths.push(DrawablePath {
+                        segments,
+                        transform: element_transform(node).then(&parent_transform),
+                        stroke,
+                        fill,
+                    });
+                }
+            }
+        }
+        "use" => collect_use(node, parent, parent_transform, uses, paths),
+        // templates (defs, symbol, clipPath, mask, marker, pattern) and everything
+        // without geometry of its own
+        _ => {}
+    }
+}
+
+// the referenced element is drawn as the only child of the `<use>`
+fn collect_use(
+    node: roxmltree::Node,
+    parent: Style,
+    parent_transform: Transform,
+    uses: &mut Vec<roxmltree::NodeId>,
+    paths: &mut Vec<DrawablePath>,
+) {
+    let target = match referenced_element(node) {
+        Some(target) if !uses.contains(&node.id()) => target,
+        _ => return,
+    };
+    let style = Style::of_element(node, parent);
+    if !style.display || style.opacity <= 0. {
+        return;
+    }
+    let x = length_attribute(node, "x").unwrap_or(0.);
+    let y = length_attribute(node, "y").unwrap_or(0.);
+    let transform = Transform::translate(x, y)
+        .then(&element_transform(node))
+        .then(&parent_transform);
+
+    uses.push(node.id());
+    if is_svg_element(target, "symbol") {
+        collect_paths(target, style, transform, uses, paths);
+    } else {
+        collect_element(target, style, transform, uses, paths);
+    }
+    uses.pop();
+}
+
+fn referenced_element<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    let href = node
+        .attribute((XLINK_NAMESPACE, "href"))
+        .or_else(|| node.attribute("href"))?;
+    let id = href.strip_prefix('#')?;
+    node.document()
+        .descendants()
+        .find(|element| element.is_element() && element.attribute("id") == Some(id))
+        .filter(|element| in_svg_namespace(*element))
+}
+
+fn shape_segments(node: roxmltree::Node) -> Vec<PathSegment> {
+    let length = |name| length_attribute(node, name).unwrap_or(0.);
+    let points = || node.attribute("points").unwrap_or("");
+    match node.tag_name().name() {
+        // everything up to the first error is drawn, as SVG renderers do
+        "path" => PathParser::from(node.attribute("d").unwrap_or(""))
+            .map_while(Result::ok)
+            .collect(),
+        "rect" => rect_segments(
+            length("x"),
+            length("y"),
+            length("width"),
+            length("height"),
+            length_attribute(node, "rx"),
+            length_attribute(node, "ry"),
+        )
+        .collect(),
+        "circle" => circle_segments(length("cx"), length("cy"), length("r")).collect(),
+        "ellipse" => {
+            ellipse_segments(length("cx"), length("cy"), length("rx"), length("ry")).collect()
+        }
+        "line" => line_segments(length("x1"), length("y1"), length("x2"), length("y2")).collect(),
+        "polyline" => polyline_segments(points()).collect(),
+        "polygon" => polygon_segments(points()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// an invalid transform attribute is ignored
+fn element_transform(node: roxmltree::Node) -> Transform {
+    node.attribute("transform")
+        .and_then(|value| svgtypes::Transform::from_str(value).ok())
+        .map(Transform::from)
+        .unwrap_or_default()
+}
+
+// in px, the user units of a document without viewBox; percentages are not supported
+fn length_attribute(node: roxmltree::Node, name: &str) -> Option<f64> {
+    node.attribute(name)
+        .and_then(|value| Length::from_str(value).ok())
+        .and_then(length_in_px)
+}
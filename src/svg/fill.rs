@@ -0,0 +1,380 @@
+//! Filling closed shapes with strokes, since a pen can only draw lines.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::distance_field::DistanceField;
+use super::line_to::LineTo;
+use super::math::distance;
+use super::point::Point;
+use super::sub_path::SubPath;
+use super::transform::Transform;
+use crate::float::ceil;
+
+/// Which points count as inside when sub-paths overlap, as the SVG `fill-rule`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FillRule {
+    /// Inside where the sub-paths wind around the point a non-zero number of times,
+    /// so holes have to run the other way round.
+    NonZero,
+    /// Inside where a ray from the point crosses the outline an odd number of times,
+    /// so every nested sub-path is a hole.
+    EvenOdd,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HatchOptions {
+    /// Direction of the lines in degrees, turning clockwise on screen from the x axis
+    /// like `Transform::rotate`.
+    pub angle: f64,
+    /// Distance between neighbouring lines, in output units.
+    pub spacing: f64,
+    /// Adds a second set of lines at right angles to the first.
+    pub cross_hatch: bool,
+    pub fill_rule: FillRule,
+}
+
+impl Default for HatchOptions {
+    fn default() -> Self {
+        HatchOptions {
+            angle: 45.,
+            spacing: 1.,
+            cross_hatch: false,
+            fill_rule: FillRule::NonZero,
+        }
+    }
+}
+
+/// Parallel lines covering the inside of the shape made of `sub_paths`. Every sub-path
+/// counts as closed, as for SVG fills. Each line is a `Fly` to its start and a `Draw`
+/// to its end, and consecutive lines run in opposite directions to keep pen-up moves
+/// short. Lines sit on a fixed grid, half a `spacing` off the origin so that they do not
+/// retrace axis aligned outlines, and neighbouring shapes hatched with the same options
+/// line up. A spacing that is not positive gives no lines.
+pub fn hatch(sub_paths: &[SubPath], options: HatchOptions) -> Vec<LineTo> {
+    let mut line_tos = Vec::new();
+    if options.spacing.is_nan() || options.spacing <= 0. {
+        return line_tos;
+    }
+    hatch_at_angle(sub_paths, options, options.angle, &mut line_tos);
+    if options.cross_hatch {
+        hatch_at_angle(sub_paths, options, options.angle + 90., &mut line_tos);
+    }
+    line_tos
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OffsetOptions {
+    /// Distance between neighbouring rings, usually the pen width, in output units.
+    pub spacing: f64,
+    pub fill_rule: FillRule,
+}
+
+impl Default for OffsetOptions {
+    fn default() -> Self {
+        OffsetOptions {
+            spacing: 1.,
+            fill_rule: FillRule::NonZero,
+        }
+    }
+}
+
+/// Rings following the outline of the shape made of `sub_paths`, each `spacing`
+/// further inside than the one before, until the shape is used up. The first ring is
+/// half a `spacing` inside, where a pen that wide just touches the outline. Rings go
+/// from the outside in, each drawn as a closed loop starting near where the pen was.
+///
+/// Rings are traced on a grid of a quarter `spacing` (coarser for very large shapes),
+/// so corners come out slightly rounded. A spacing that is not positive gives nothing.
+pub fn concentric(sub_paths: &[SubPath], options: OffsetOptions) -> Vec<LineTo> {
+    let mut line_tos = Vec::new();
+    let mut pen = Point::ZERO;
+    for ring in offset_rings(sub_paths, options) {
+        let points = starting_near(&ring.points, pen);
+        line_tos.push(LineTo::Fly(points[0]));
+        line_tos.extend(points[1..].iter().map(|point| LineTo::Draw(*point)));
+        pen = points[0];
+    }
+    line_tos
+}
+
+/// Like `concentric`, but the rings are joined into a spiral that runs inwards with
+/// the pen down. The outermost and innermost rings are drawn as whole loops at either
+/// end. The pen is only lifted where the shape splits into parts as it shrinks, and
+/// every part gets a spiral of its own.
+pub fn spiral(sub_paths: &[SubPath], options: OffsetOptions) -> Vec<LineTo> {
+    let rings = offset_rings(sub_paths, options);
+    let mut children = vec![0; rings.len()];
+    for ring in rings.iter() {
+        if let Some(parent) = ring.parent {
+            children[parent] += 1;
+        }
+    }
+
+    let mut line_tos = Vec::new();
+    let mut pen = Point::ZERO;
+    for (index, ring) in rings.iter().enumerate() {
+        let continues_parent = matches!(ring.parent, Some(parent) if children[parent] == 1);
+        if continues_parent {
+            continue;
+        }
+
+        // the chain of rings with a single child each, starting here
+        let mut outer = starting_near(&ring.points, pen);
+        line_tos.push(LineTo::Fly(outer[0]));
+        line_tos.extend(outer[1..].iter().map(|point| LineTo::Draw(*point)));
+        let mut current = index;
+        while children[current] == 1 {
+            let child = (current + 1..rings.len())
+                .find(|child| rings[*child].parent == Some(current))
+                .expect("counted child");
+            let mut inner = starting_near(&rings[child].points, outer[0]);
+            if (signed_area(&inner) > 0.) != (signed_area(&outer) > 0.) {
+                inner.reverse();
+            }
+            line_tos.extend(spiral_turn(&outer, &inner).map(LineTo::Draw));
+            outer = inner;
+            current = child;
+        }
+        line_tos.extend(outer[1..].iter().map(|point| LineTo::Draw(*point)));
+        pen = outer[0];
+    }
+    line_tos
+}
+
+// === private members ===
+
+pub(crate) struct Edge {
+    pub(crate) start: Point,
+    pub(crate) end: Point,
+}
+
+fn hatch_at_angle(
+    sub_paths: &[SubPath],
+    options: HatchOptions,
+    angle: f64,
+    line_tos: &mut Vec<LineTo>,
+) {
+    // in hatch space the lines are horizontal
+    let to_hatch_space = Transform::rotate(-angle);
+    let from_hatch_space = Transform::rotate(angle);
+    let edges = edges(sub_paths, &to_hatch_space);
+    let (min_y, max_y) = match y_range(&edges) {
+        Some(range) => range,
+        None => return,
+    };
+
+    let first_line = ceil(min_y / options.spacing - 0.5) as i64;
+    let mut crossings: Vec<(f64, i32)> = Vec::new();
+    let mut reversed = false;
+    for line in first_line.. {
+        let y = (line as f64 + 0.5) * options.spacing;
+        if y > max_y {
+            break;
+        }
+
+        crossings.clear();
+        crossings.extend(edges.iter().filter_map(|edge| crossing(edge, y)));
+        crossings.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let first_new = line_tos.len();
+        let mut winding = 0;
+        let mut entered_at = 0.;
+        for &(x, direction) in crossings.iter() {
+            let was_inside = is_inside(winding, options.fill_rule);
+            winding += direction;
+            match (was_inside, is_inside(winding, options.fill_rule)) {
+                (false, true) => entered_at = x,
+                (true, false) if x > entered_at => {
+                    line_tos.push(LineTo::Fly(
+                        from_hatch_space.apply(Point::new(entered_at, y)),
+                    ));
+                    line_tos.push(LineTo::Draw(from_hatch_space.apply(Point::new(x, y))));
+                }
+                _ => {}
+            }
+        }
+
+        if first_new < line_tos.len() {
+            if reversed {
+                reverse_line_pairs(&mut line_tos[first_new..]);
+            }
+            reversed = !reversed;
+        }
+    }
+}
+
+// the sides of all sub-paths, each closed if it is not already, seen through `transform`
+pub(crate) fn edges(sub_paths: &[SubPath], transform: &Transform) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for sub_path in sub_paths {
+        let points = &sub_path.points;
+        for (index, point) in points.iter().enumerate() {
+            let next = points[(index + 1) % points.len()];
+            if *point != next {
+                edges.push(Edge {
+                    start: transform.apply(*point),
+                    end: transform.apply(next),
+                });
+            }
+        }
+    }
+    edges
+}
+
+fn y_range(edges: &[Edge]) -> Option<(f64, f64)> {
+    edges.iter().fold(None, |range, edge| {
+        let (low, high) = (edge.start.y.min(edge.end.y), edge.start.y.max(edge.end.y));
+        Some(match range {
+            None => (low, high),
+            Some((min, max)) => (min.min(low), max.max(high)),
+        })
+    })
+}
+
+// where the edge crosses the line at `y`, and +1 or -1 for its direction. Edges own
+// their lower end only, so a line through a vertex is counted once.
+pub(crate) fn crossing(edge: &Edge, y: f64) -> Option<(f64, i32)> {
+    let (start, end) = (edge.start, edge.end);
+    let (low, high, direction) = if start.y < end.y {
+        (start.y, end.y, 1)
+    } else {
+        (end.y, start.y, -1)
+    };
+    if !(low <= y && y < high) {
+        return None;
+    }
+    let x = start.x + (y - start.y) * (end.x - start.x) / (end.y - start.y);
+    Some((x, direction))
+}
+
+pub(crate) fn is_inside(winding: i32, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+// the `Fly`, `Draw` pairs of one line, walked from the other end
+fn reverse_line_pairs(line_tos: &mut [LineTo]) {
+    line_tos.reverse();
+    for pair in line_tos.chunks_mut(2) {
+        if let [LineTo::Draw(start), LineTo::Fly(end)] = *pair {
+            pair[0] = LineTo::Fly(start);
+            pair[1] = LineTo::Draw(end);
+        }
+    }
+}
+
+struct Ring {
+    points: Vec<Point>,
+    /// The ring one level further out that this one shrank from.
+    parent: Option<usize>,
+}
+
+// all rings, outermost level first
+fn offset_rings(sub_paths: &[SubPath], options: OffsetOptions) -> Vec<Ring> {
+    let mut rings: Vec<Ring> = Vec::new();
+    if options.spacing.is_nan() || options.spacing <= 0. {
+        return rings;
+    }
+    let field = match DistanceField::new(sub_paths, options.fill_rule, options.spacing) {
+        Some(field) => field,
+        None => return rings,
+    };
+
+    let max_value = field.max_value();
+    let mut previous_level = 0..0;
+    let mut level = 0.5 * options.spacing;
+    while level < max_value {
+        let level_start = rings.len();
+        for points in field.contours(level) {
+            let parent = previous_level.clone().min_by(|a: &usize, b: &usize| {
+                let distance_a = distance_to_points(points[0], &rings[*a].points);
+                let distance_b = distance_to_points(points[0], &rings[*b].points);
+                distance_a
+                    .partial_cmp(&distance_b)
+                    .unwrap_or(Ordering::Equal)
+            });
+            rings.push(Ring { points, parent });
+        }
+        previous_level = level_start..rings.len();
+        level += options.spacing;
+    }
+    rings
+}
+
+fn distance_to_points(point: Point, points: &[Point]) -> f64 {
+    points
+        .iter()
+        .map(|other| distance(point, *other))
+        .fold(f64::INFINITY, f64::min)
+}
+
+// the closed ring with its first point moved next to `near`
+fn starting_near(ring: &[Point], near: Point) -> Vec<Point> {
+    let open = &ring[..ring.len() - 1];
+    let start = (0..open.len())
+        .min_by(|a, b| {
+            let distance_a = distance(open[*a], near);
+            let distance_b = distance(open[*b], near);
+            distance_a
+                .partial_cmp(&distance_b)
+                .unwrap_or(Ordering::Equal)
+        })
+        .unwrap_or(0);
+    let mut points: Vec<Point> = open[start..]
+        .iter()
+        .chain(&open[..start])
+        .copied()
+        .collect();
+    points.push(points[0]);
+    points
+}
+
+// twice the area enclosed by a closed ring, its sign telling the direction
+fn signed_area(ring: &[Point]) -> f64 {
+    ring.windows(2)
+        .map(|pair| pair[0].x * pair[1].y - pair[1].x * pair[0].y)
+        .sum()
+}
+
+// one lap from the start of `outer` to the start of `inner`, sliding from one ring to
+// the other at the same fraction of their lengths
+fn spiral_turn<'a>(outer: &'a [Point], inner: &'a [Point]) -> impl Iterator<Item = Point> + 'a {
+    let samples = outer.len().max(inner.len());
+    let (outer_lengths, inner_lengths) = (running_lengths(outer), running_lengths(inner));
+    (1..=samples).map(move |sample| {
+        let fraction = sample as f64 / samples as f64;
+        let from = point_at_fraction(outer, &outer_lengths, fraction);
+        let to = point_at_fraction(inner, &inner_lengths, fraction);
+        from + (to - from) * fraction
+    })
+}
+
+fn running_lengths(points: &[Point]) -> Vec<f64> {
+    let mut total = 0.;
+    let mut lengths = Vec::with_capacity(points.len());
+    lengths.push(0.);
+    for pair in points.windows(2) {
+        total += distance(pair[0], pair[1]);
+        lengths.push(total);
+    }
+    lengths
+}
+
+fn point_at_fraction(points: &[Point], lengths: &[f64], fraction: f64) -> Point {
+    let total = lengths[lengths.len() - 1];
+    let target = fraction * total;
+    let index = lengths
+        .partition_point(|length| *length < target)
+        .clamp(1, points.len() - 1);
+    let (start, end) = (lengths[index - 1], lengths[index]);
+    let ratio = if end > start {
+        (target - start) / (end - start)
+    } else {
+        0.
+    };
+    points[index - 1] + (points[index] - points[index - 1]) * ratio
+}
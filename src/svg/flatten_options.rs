@@ -0,0 +1,52 @@
+use super::transform::Transform;
+
+/// How curves are turned into chords.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Flattening {
+    /// Sample every curve with the same (positive) parameter step, whatever its size.
+    FixedStep(f64),
+    /// Use as few chords as possible while keeping each one within the given
+    /// distance (in output units) of the real curve.
+    Tolerance(f64),
+    /// Emit points evenly spaced along the path, roughly the given distance apart.
+    /// Every curve and drawn line still ends exactly on its end point.
+    ArcLength(f64),
+}
+
+/// Knobs for turning path segments into a `LineTo` stream.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FlattenOptions {
+    pub flattening: Flattening,
+    /// Curve points closer than this to the previous point are skipped. The last
+    /// point of a curve is always kept.
+    pub min_segment_length: f64,
+    /// How far (in output units) a control point may be from the chord for the curve to
+    /// count as straight. Beyond the chord ends that is the distance to the nearer end.
+    pub straight_tolerance: f64,
+    /// Draw curves whose control points lie on the chord as a single line.
+    pub collapse_straight_curves: bool,
+    /// Applied to the geometry before it is flattened, so tolerances and spacings are
+    /// measured after the transform.
+    pub transform: Transform,
+}
+
+impl FlattenOptions {
+    pub fn with_tolerance(tolerance: f64) -> Self {
+        FlattenOptions {
+            flattening: Flattening::Tolerance(tolerance),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions {
+            flattening: Flattening::FixedStep(0.001),
+            min_segment_length: 0.,
+            straight_tolerance: 0.05,
+            collapse_straight_curves: true,
+            transform: Transform::IDENTITY,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::point::Point;
+use crate::scalar::Scalar;
+
+// differences are rebuilt from the polynomial this often, so that rounding in the
+// running sums can not pile up over long curves
+const RESTART_TICKS: usize = 128;
+
+/// Walks a polynomial curve of degree three or less in equal parameter steps, like
+/// `TickTimer` with `CurvePoint::at`, but with six additions per step instead of
+/// evaluating the polynomial.
+pub struct ForwardDifferences<S: Scalar = f64> {
+    // B(t) = a * t^3 + b * t^2 + c * t + d
+    a: Point,
+    b: Point,
+    c: Point,
+    d: Point,
+    ticks: usize,
+    tick: usize,
+    steps_since_restart: usize,
+    // value, first, second and third difference
+    x: [S::Difference; 4],
+    y: [S::Difference; 4],
+}
+
+impl<S: Scalar> ForwardDifferences<S> {
+    pub fn quadratic(start: Point, p1: Point, end: Point, ticks: usize) -> Self {
+        let b = start - p1 * 2. + end;
+        let c = (p1 - start) * 2.;
+        ForwardDifferences::new(Point::ZERO, b, c, start, ticks)
+    }
+
+    pub fn cubic(start: Point, p1: Point, p2: Point, end: Point, ticks: usize) -> Self {
+        let a = (p1 - p2) * 3. + end - start;
+        let b = (start - p1 * 2. + p2) * 3.;
+        let c = (p1 - start) * 3.;
+        ForwardDifferences::new(a, b, c, start, ticks)
+    }
+
+    fn new(a: Point, b: Point, c: Point, d: Point, ticks: usize) -> Self {
+        let zero = S::difference_from_f64(0.);
+        let mut differences = ForwardDifferences {
+            a,
+            b,
+            c,
+            d,
+            ticks: ticks.max(1),
+            tick: 0,
+            steps_since_restart: 0,
+            x: [zero; 4],
+            y: [zero; 4],
+        };
+        differences.restart();
+        differences
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.ticks
+    }
+
+    // exact differences at the current tick, computed in f64
+    fn restart(&mut self) {
+        self.steps_since_restart = 0;
+        let h = 1. / self.ticks as f64;
+        let t = self.tick as f64 * h;
+        let value = ((self.a * t + self.b) * t + self.c) * t + self.d;
+        let first = self.a * (3. * t * t * h + 3. * t * h * h + h * h * h)
+            + self.b * (2. * t * h + h * h)
+            + self.c * h;
+        let second = self.a * (6. * t * h * h + 6. * h * h * h) + self.b * (2. * h * h);
+        let third = self.a * (6. * h * h * h);
+
+        self.x = [value.x, first.x, second.x, third.x].map(S::difference_from_f64);
+        self.y = [value.y, first.y, second.y, third.y].map(S::difference_from_f64);
+    }
+}
+
+impl<S: Scalar> Iterator for ForwardDifferences<S> {
+    type Item = Point<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished() {
+            return None;
+        }
+        self.tick += 1;
+        self.steps_since_restart += 1;
+        if self.steps_since_restart == RESTART_TICKS || self.tick == self.ticks {
+            self.restart();
+        } else {
+            for axis in [&mut self.x, &mut self.y] {
+                axis[0] = axis[0] + axis[1];
+                axis[1] = axis[1] + axis[2];
+                axis[2] = axis[2] + axis[3];
+            }
+        }
+        Some(Point::new(
+            S::from_difference(self.x[0]),
+            S::from_difference(self.y[0]),
+        ))
+    }
+}
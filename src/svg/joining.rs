@@ -0,0 +1,127 @@
+//! Merging sub-paths that touch end to end, so one stroke is drawn with one pen drop.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use super::line_to::LineTo;
+use super::math::distance;
+use super::point::Point;
+use super::sub_path::{line_tos_from_sub_paths, sub_paths, PointGrid, SubPath};
+
+/// Joins open sub-paths where one ends within `tolerance` of where another starts or
+/// ends, reversing sub-paths where that makes them fit. Sub-paths anywhere in the
+/// input can be joined; a joined stroke takes the place of its earliest piece, and
+/// where several pieces meet at one point the earliest one wins. Gaps up to
+/// `tolerance` are bridged with a short drawn line. Closed sub-paths are left alone.
+pub fn join_sub_paths(sub_paths: Vec<SubPath>, tolerance: f64) -> Vec<SubPath> {
+    let grid = EndpointGrid::new(&sub_paths, tolerance);
+    let mut used: Vec<bool> = sub_paths
+        .iter()
+        .map(|sub_path| sub_path.is_closed() || sub_path.points.is_empty())
+        .collect();
+    let mut joined = Vec::with_capacity(sub_paths.len());
+    for index in 0..sub_paths.len() {
+        if sub_paths[index].is_closed() {
+            joined.push(sub_paths[index].clone());
+            continue;
+        }
+        if used[index] {
+            continue;
+        }
+        used[index] = true;
+
+        let mut stroke = sub_paths[index].points.clone();
+        extend(&mut stroke, &sub_paths, &grid, &mut used, tolerance);
+        stroke.reverse();
+        extend(&mut stroke, &sub_paths, &grid, &mut used, tolerance);
+        stroke.reverse();
+        joined.push(SubPath { points: stroke });
+    }
+    joined
+}
+
+/// `join_sub_paths` for a flattened stream, cut up and put back together as described
+/// in `sub_path`.
+pub fn join_line_tos(line_tos: impl IntoIterator<Item = LineTo>, tolerance: f64) -> Vec<LineTo> {
+    line_tos_from_sub_paths(&join_sub_paths(sub_paths(line_tos), tolerance))
+}
+
+// === private members ===
+
+// appends unused sub-paths to the end of `stroke` for as long as one touches it
+fn extend(
+    stroke: &mut Vec<Point>,
+    sub_paths: &[SubPath],
+    grid: &EndpointGrid,
+    used: &mut [bool],
+    tolerance: f64,
+) {
+    while let Some((path, at_end)) = grid.touching(stroke[stroke.len() - 1], used, tolerance) {
+        used[path] = true;
+        let points = &sub_paths[path].points;
+        let before = stroke.len();
+        if at_end {
+            stroke.extend(points.iter().rev());
+        } else {
+            stroke.extend(points.iter());
+        }
+        if stroke[before - 1] == stroke[before] {
+            stroke.remove(before);
+        }
+    }
+}
+
+/// First and last points of all sub-paths, bucketed by position.
+struct EndpointGrid {
+    grid: PointGrid,
+    endpoints: Vec<Point>,
+    /// Cell to indices into `endpoints`: `2 * path` for starts, `2 * path + 1` for ends.
+    cells: BTreeMap<(i64, i64), Vec<usize>>,
+}
+
+impl EndpointGrid {
+    fn new(sub_paths: &[SubPath], tolerance: f64) -> Self {
+        let mut endpoint_grid = EndpointGrid {
+            grid: PointGrid::new(tolerance),
+            endpoints: Vec::with_capacity(2 * sub_paths.len()),
+            cells: BTreeMap::new(),
+        };
+        for sub_path in sub_paths {
+            let points = &sub_path.points;
+            let first = points.first().copied().unwrap_or(Point::ZERO);
+            let last = points.last().copied().unwrap_or(Point::ZERO);
+            for point in [first, last] {
+                // empty sub-paths keep their place in `endpoints` but are never found
+                if !points.is_empty() {
+                    let key = endpoint_grid.grid.key(point);
+                    endpoint_grid
+                        .cells
+                        .entry(key)
+                        .or_default()
+                        .push(endpoint_grid.endpoints.len());
+                }
+                endpoint_grid.endpoints.push(point);
+            }
+        }
+        endpoint_grid
+    }
+
+    // the earliest unused sub-path with an endpoint within `tolerance`, and whether
+    // that endpoint is its end
+    fn touching(&self, point: Point, used: &[bool], tolerance: f64) -> Option<(usize, bool)> {
+        let mut touching: Option<usize> = None;
+        for key in self.grid.around(point) {
+            let candidates = match self.cells.get(&key) {
+                Some(candidates) => candidates,
+                None => continue,
+            };
+            for &endpoint in candidates {
+                if used[endpoint / 2] || distance(point, self.endpoints[endpoint]) > tolerance {
+                    continue;
+                }
+                touching = Some(touching.map_or(endpoint, |touching| touching.min(endpoint)));
+            }
+        }
+        touching.map(|endpoint| (endpoint / 2, endpoint % 2 == 1))
+    }
+}
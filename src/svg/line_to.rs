@@ -0,0 +1,110 @@
+#[cfg(any(feature = "alloc", feature = "svgtypes"))]
+use super::math::{angle_around, arc_sweep};
+use super::point::Point;
+#[cfg(any(feature = "alloc", feature = "svgtypes"))]
+use crate::float::{ceil, cos, sin};
+use crate::scalar::Scalar;
+#[cfg(any(feature = "alloc", feature = "svgtypes"))]
+use core::f64::consts::PI;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LineTo<S: Scalar = f64> {
+    Fly(Point<S>),
+    Draw(Point<S>),
+    Erase(Point<S>),
+    /// Circular arc drawn from the current point.
+    Arc(CircularArc<S>),
+}
+
+/// Circular arc in the style of G2/G3 moves.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CircularArc<S: Scalar = f64> {
+    pub end: Point<S>,
+    pub center: Point<S>,
+    pub radius: S,
+    /// Direction on screen, with the y axis pointing down as in SVG. Matches `sweep-flag = 1`.
+    pub clockwise: bool,
+}
+
+impl<S: Scalar> LineTo<S> {
+    /// Where the pen is after this move.
+    pub fn target(&self) -> Point<S> {
+        match *self {
+            LineTo::Fly(point) => point,
+            LineTo::Draw(point) => point,
+            LineTo::Erase(point) => point,
+            LineTo::Arc(arc) => arc.end,
+        }
+    }
+
+    /// Same move with coordinates in another scalar type.
+    pub fn cast<T: Scalar>(self) -> LineTo<T> {
+        match self {
+            LineTo::Fly(point) => LineTo::Fly(point.cast()),
+            LineTo::Draw(point) => LineTo::Draw(point.cast()),
+            LineTo::Erase(point) => LineTo::Erase(point.cast()),
+            LineTo::Arc(arc) => LineTo::Arc(CircularArc {
+                end: arc.end.cast(),
+                center: arc.center.cast(),
+                radius: T::from_f64(arc.radius.to_f64()),
+                clockwise: arc.clockwise,
+            }),
+        }
+    }
+}
+
+// === private members ===
+
+// circular arcs are replaced by chords spanning at most this angle
+#[cfg(any(feature = "alloc", feature = "svgtypes"))]
+const ARC_STEP: f64 = PI / 90.;
+
+/// The points of an arc drawn from `start`, at most 2 degrees apart and ending exactly
+/// on its end.
+#[cfg(any(feature = "alloc", feature = "svgtypes"))]
+pub(crate) struct ArcChords {
+    center: Point,
+    radius: f64,
+    start_angle: f64,
+    sweep: f64,
+    end: Point,
+    steps: usize,
+    step: usize,
+}
+
+#[cfg(any(feature = "alloc", feature = "svgtypes"))]
+impl ArcChords {
+    pub(crate) fn new(start: Point, arc: &CircularArc) -> Self {
+        let start_angle = angle_around(arc.center, start);
+        let sweep = arc_sweep(
+            angle_around(arc.center, arc.end) - start_angle,
+            arc.clockwise,
+        );
+        ArcChords {
+            center: arc.center,
+            radius: arc.radius,
+            start_angle,
+            sweep,
+            end: arc.end,
+            steps: ceil(sweep.abs() / ARC_STEP).max(1.) as usize,
+            step: 0,
+        }
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "svgtypes"))]
+impl Iterator for ArcChords {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.step >= self.steps {
+            return None;
+        }
+        self.step += 1;
+        if self.step == self.steps {
+            return Some(self.end);
+        }
+        let angle = self.start_angle + self.sweep * self.step as f64 / self.steps as f64;
+        Some(self.center + Point::new(cos(angle), sin(angle)) * self.radius)
+    }
+}
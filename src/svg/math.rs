@@ -1,106 +1,233 @@
+use super::forward_differences::ForwardDifferences;
 use super::point::Point;
+use crate::float::{acos, atan2, ceil, cos, sin, sqrt};
+use crate::scalar::Scalar;
 use core::f64::consts::PI;
 
-pub trait CurvePoint {
-    fn at(&self, time: f64) -> Point;
+pub trait CurvePoint<S: Scalar = f64> {
+    fn at(&self, time: S) -> Point<S>;
+
+    /// Derivative of `at` with respect to time.
+    fn derivative(&self, time: S) -> Point<S>;
+
+    /// Number of equal parameter steps needed so that no chord strays from the curve
+    /// by more than `tolerance`.
+    fn ticks_for_tolerance(&self, tolerance: f64) -> usize;
+
+    /// Points at `1/ticks, 2/ticks, ..., 1.0` computed incrementally, for curves that
+    /// support it.
+    fn forward_differences(&self, _ticks: usize) -> Option<ForwardDifferences<S>> {
+        None
+    }
+}
+
+const MAX_TICKS: usize = 1 << 16;
+
+// chord deviation over a parameter step `h` is bounded by `max|B''| * h^2 / 8`
+fn ticks_for_second_derivative(max_second_derivative: f64, tolerance: f64) -> usize {
+    clamp_ticks(ceil(sqrt(max_second_derivative / (8. * tolerance))))
+}
+
+pub fn clamp_ticks(ticks: f64) -> usize {
+    if ticks.is_nan() || ticks < 1. {
+        1
+    } else if ticks > MAX_TICKS as f64 {
+        MAX_TICKS
+    } else {
+        ticks as usize
+    }
 }
 
-pub struct SquareCurve {
-    start: Point,
-    p1: Point,
-    end: Point,
+pub fn length<S: Scalar>(point: Point<S>) -> S {
+    (point.x * point.x + point.y * point.y).sqrt()
 }
 
-impl SquareCurve {
-    pub fn new(start: Point, p1: Point, end: Point) -> Self {
+pub fn distance<S: Scalar>(from: Point<S>, to: Point<S>) -> S {
+    length(to - from)
+}
+
+pub fn distance_to_segment(start: Point, end: Point, point: Point) -> f64 {
+    let segment = end - start;
+    let segment_square = sqr(segment.x) + sqr(segment.y);
+    if segment_square == 0. {
+        return distance(start, point);
+    }
+    let projection =
+        ((point.x - start.x) * segment.x + (point.y - start.y) * segment.y) / segment_square;
+    let clamped = projection.clamp(0., 1.);
+    distance(start + segment * clamped, point)
+}
+
+// Bezier points are evaluated relative to the start point and scalar weights are
+// multiplied together before they touch a coordinate, so fixed-point rounding scales
+// with the size of the curve and not with its distance from the origin
+
+pub struct SquareCurve<S: Scalar = f64> {
+    start: Point<S>,
+    p1: Point<S>,
+    end: Point<S>,
+}
+
+impl<S: Scalar> SquareCurve<S> {
+    pub fn new(start: Point<S>, p1: Point<S>, end: Point<S>) -> Self {
         SquareCurve { start, p1, end }
     }
 }
 
-impl CurvePoint for SquareCurve {
-    fn at(&self, time: f64) -> Point {
-        let diff = 1. - time;
+impl<S: Scalar> CurvePoint<S> for SquareCurve<S> {
+    fn at(&self, time: S) -> Point<S> {
+        let two = S::ONE + S::ONE;
+        let diff = S::ONE - time;
         let square_t = time * time;
-        let square_diff = diff * diff;
-        self.start * square_diff + self.p1 * 2. * time * diff + self.end * square_t
+        self.start
+            + (self.p1 - self.start) * (two * time * diff)
+            + (self.end - self.start) * square_t
+    }
+
+    fn derivative(&self, time: S) -> Point<S> {
+        let two = S::ONE + S::ONE;
+        (self.p1 - self.start) * (two * (S::ONE - time)) + (self.end - self.p1) * (two * time)
+    }
+
+    fn ticks_for_tolerance(&self, tolerance: f64) -> usize {
+        let (start, p1, end) = (self.start.cast::<f64>(), self.p1.cast(), self.end.cast());
+        let second_derivative = (start - p1 * 2. + end) * 2.;
+        ticks_for_second_derivative(length(second_derivative), tolerance)
+    }
+
+    fn forward_differences(&self, ticks: usize) -> Option<ForwardDifferences<S>> {
+        Some(ForwardDifferences::quadratic(
+            self.start.cast(),
+            self.p1.cast(),
+            self.end.cast(),
+            ticks,
+        ))
     }
 }
 
-pub struct CubicCurve {
-    start: Point,
-    p1: Point,
-    p2: Point,
-    end: Point,
+pub struct CubicCurve<S: Scalar = f64> {
+    start: Point<S>,
+    p1: Point<S>,
+    p2: Point<S>,
+    end: Point<S>,
 }
 
-impl CubicCurve {
-    pub fn new(start: Point, p1: Point, p2: Point, end: Point) -> Self {
+impl<S: Scalar> CubicCurve<S> {
+    pub fn new(start: Point<S>, p1: Point<S>, p2: Point<S>, end: Point<S>) -> Self {
         CubicCurve { start, p1, p2, end }
     }
 }
 
-impl CurvePoint for CubicCurve {
-    fn at(&self, time: f64) -> Point {
-        let diff = 1. - time;
+impl<S: Scalar> CurvePoint<S> for CubicCurve<S> {
+    fn at(&self, time: S) -> Point<S> {
+        let three = S::ONE + S::ONE + S::ONE;
+        let diff = S::ONE - time;
         let square_t = time * time;
         let cube_t = square_t * time;
         let square_diff = diff * diff;
-        let cube_diff = square_diff * diff;
 
-        self.start * cube_diff
-            + self.p1 * 3. * time * square_diff
-            + self.p2 * 3. * square_t * diff
-            + self.end * cube_t
+        self.start
+            + (self.p1 - self.start) * (three * time * square_diff)
+            + (self.p2 - self.start) * (three * square_t * diff)
+            + (self.end - self.start) * cube_t
+    }
+
+    fn derivative(&self, time: S) -> Point<S> {
+        let three = S::ONE + S::ONE + S::ONE;
+        let diff = S::ONE - time;
+        (self.p1 - self.start) * (three * diff * diff)
+            + (self.p2 - self.p1) * ((three + three) * diff * time)
+            + (self.end - self.p2) * (three * time * time)
+    }
+
+    fn ticks_for_tolerance(&self, tolerance: f64) -> usize {
+        let (start, p1, p2, end) = (
+            self.start.cast::<f64>(),
+            self.p1.cast(),
+            self.p2.cast(),
+            self.end.cast(),
+        );
+        let start_bend = length(start - p1 * 2. + p2);
+        let end_bend = length(p1 - p2 * 2. + end);
+        ticks_for_second_derivative(6. * start_bend.max(end_bend), tolerance)
+    }
+
+    fn forward_differences(&self, ticks: usize) -> Option<ForwardDifferences<S>> {
+        Some(ForwardDifferences::cubic(
+            self.start.cast(),
+            self.p1.cast(),
+            self.p2.cast(),
+            self.end.cast(),
+            ticks,
+        ))
     }
 }
 
-pub struct EllipseCurve {
-    start_angle: f64,
-    sweep_angle: f64,
-    rx_abs: f64,
-    ry_abs: f64,
-    x_rad_rotation: f64,
-    center_x: f64,
-    center_y: f64,
+pub struct EllipseCurve<S: Scalar = f64> {
+    start_angle: S,
+    sweep_angle: S,
+    rx_abs: S,
+    ry_abs: S,
+    rotation_cos: S,
+    rotation_sin: S,
+    center_x: S,
+    center_y: S,
 }
 
-impl EllipseCurve {
+impl<S: Scalar> EllipseCurve<S> {
     pub fn new(
-        start_angle: f64,
-        sweep_angle: f64,
-        rx_abs: f64,
-        ry_abs: f64,
-        x_rad_rotation: f64,
-        center_x: f64,
-        center_y: f64,
+        start_angle: S,
+        sweep_angle: S,
+        rx_abs: S,
+        ry_abs: S,
+        x_rad_rotation: S,
+        center_x: S,
+        center_y: S,
     ) -> Self {
         EllipseCurve {
             start_angle,
             sweep_angle,
             rx_abs,
             ry_abs,
-            x_rad_rotation,
+            rotation_cos: x_rad_rotation.cos(),
+            rotation_sin: x_rad_rotation.sin(),
             center_x,
             center_y,
         }
     }
 }
 
-impl CurvePoint for EllipseCurve {
-    fn at(&self, time: f64) -> Point {
+impl<S: Scalar> CurvePoint<S> for EllipseCurve<S> {
+    fn at(&self, time: S) -> Point<S> {
         let angle = self.start_angle + self.sweep_angle * time;
         let ellipse_component_x = self.rx_abs * angle.cos();
         let ellipse_component_y = self.ry_abs * angle.sin();
 
-        let point_x = self.x_rad_rotation.cos() * ellipse_component_x
-            - self.x_rad_rotation.sin() * ellipse_component_y
+        let point_x = self.rotation_cos * ellipse_component_x
+            - self.rotation_sin * ellipse_component_y
             + self.center_x;
-        let point_y = self.x_rad_rotation.sin() * ellipse_component_x
-            + self.x_rad_rotation.cos() * ellipse_component_y
+        let point_y = self.rotation_sin * ellipse_component_x
+            + self.rotation_cos * ellipse_component_y
             + self.center_y;
 
         Point::new(point_x, point_y)
     }
+
+    fn derivative(&self, time: S) -> Point<S> {
+        let angle = self.start_angle + self.sweep_angle * time;
+        let ellipse_component_x = -self.rx_abs * angle.sin() * self.sweep_angle;
+        let ellipse_component_y = self.ry_abs * angle.cos() * self.sweep_angle;
+
+        Point::new(
+            self.rotation_cos * ellipse_component_x - self.rotation_sin * ellipse_component_y,
+            self.rotation_sin * ellipse_component_x + self.rotation_cos * ellipse_component_y,
+        )
+    }
+
+    fn ticks_for_tolerance(&self, tolerance: f64) -> usize {
+        let max_radius = self.rx_abs.to_f64().max(self.ry_abs.to_f64());
+        ticks_for_second_derivative(max_radius * sqr(self.sweep_angle.to_f64()), tolerance)
+    }
 }
 
 pub fn ellipse_support_calc(
@@ -127,13 +254,13 @@ pub fn ellipse_support_calc(
     let dy = (start_y - end_y) / 2.;
 
     // Step #1: Compute transformedPoint
-    let dx_rotated = x_rad_rotation.cos() * dx + x_rad_rotation.sin() * dy;
-    let dy_rotated = -x_rad_rotation.sin() * dx + x_rad_rotation.cos() * dy;
+    let dx_rotated = cos(x_rad_rotation) * dx + sin(x_rad_rotation) * dy;
+    let dy_rotated = -sin(x_rad_rotation) * dx + cos(x_rad_rotation) * dy;
 
     let radii_check = sqr(dx_rotated) / sqr(rx_abs) + sqr(dy_rotated) / sqr(ry_abs);
     if radii_check > 1.0 {
-        rx_abs = radii_check.sqrt() * rx_abs;
-        ry_abs = radii_check.sqrt() * ry_abs;
+        rx_abs = sqrt(radii_check) * rx_abs;
+        ry_abs = sqrt(radii_check) * ry_abs;
     }
 
     // Step #2: Compute transformedCenter
@@ -146,7 +273,7 @@ pub fn ellipse_support_calc(
     };
 
     let center_coef = {
-        let sqrt = center_radicand.sqrt();
+        let sqrt = sqrt(center_radicand);
         if large_arc != sweep {
             sqrt
         } else {
@@ -157,11 +284,10 @@ pub fn ellipse_support_calc(
     let center_y_rotated = center_coef * (-ry_abs * dx_rotated / rx_abs);
 
     // Step #3: Compute center
-    let center_x = x_rad_rotation.cos() * center_x_rotated
-        - x_rad_rotation.sin() * center_y_rotated
+    let center_x = cos(x_rad_rotation) * center_x_rotated - sin(x_rad_rotation) * center_y_rotated
         + ((start_x + end_x) / 2.);
-    let center_y = x_rad_rotation.sin() * center_x_rotated
-        + x_rad_rotation.cos() * center_y_rotated
+    let center_y = sin(x_rad_rotation) * center_x_rotated
+        + cos(x_rad_rotation) * center_y_rotated
         + ((start_y + end_y) / 2.);
 
     // Step #4: Compute start/sweep angles
@@ -198,31 +324,39 @@ pub fn sqr(x: f64) -> f64 {
 
 pub fn angle_between(start: Point, end: Point) -> f64 {
     let p = start.x * end.x + start.y * end.y;
-    let n = ((sqr(start.x) + sqr(start.y)) * (sqr(end.x) + sqr(end.y))).sqrt();
+    let n = sqrt((sqr(start.x) + sqr(start.y)) * (sqr(end.x) + sqr(end.y)));
     let sign = if start.x * end.y - start.y * end.x < 0. {
         -1.
     } else {
         1.
     };
-    let angle = sign * (p / n).acos();
+    let angle = sign * acos(p / n);
     return angle;
 }
 
-const EPSILON: f64 = 0.05;
-pub fn is_point_on_lane(lane_start: Point, lane_end: Point, p: &Point) -> bool {
-    let vector = lane_end - lane_start;
+/// Angle of `point` as seen from `center`.
+pub fn angle_around(center: Point, point: Point) -> f64 {
+    atan2(point.y - center.y, point.x - center.x)
+}
 
-    let left_part = if vector.x == 0. {
-        0.
+/// `angle`, a difference of angles around the centre of an arc, moved by whole turns
+/// into the direction of the arc: (0, 2π] for clockwise arcs, which turn towards growing
+/// angles with y pointing down, [-2π, 0) otherwise. An arc that ends where it starts
+/// goes all the way round.
+pub fn arc_sweep(angle: f64, clockwise: bool) -> f64 {
+    let angle = angle % (2. * PI);
+    if clockwise && angle <= 0. {
+        angle + 2. * PI
+    } else if !clockwise && angle >= 0. {
+        angle - 2. * PI
     } else {
-        (p.x - lane_start.x) / vector.x
-    };
-    let right_part = if vector.y == 0. {
-        0.
-    } else {
-        (p.y - lane_start.y) / vector.y
-    };
+        angle
+    }
+}
 
-    let is_on_lane = left_part - right_part;
-    is_on_lane.abs() < EPSILON
+/// Whether `p` is within `tolerance` of the segment between `lane_start` and `lane_end`:
+/// its perpendicular distance when it projects inside the segment, otherwise its distance
+/// to the nearer end. A zero-length segment is treated as a single point.
+pub fn is_point_on_lane(lane_start: Point, lane_end: Point, p: &Point, tolerance: f64) -> bool {
+    distance_to_segment(lane_start, lane_end, *p) <= tolerance
 }
@@ -1,4 +1,33 @@
-mod math;
+mod arc_length_timer;
+pub mod biarc;
+#[cfg(feature = "alloc")]
+pub mod deduplicate;
+#[cfg(feature = "alloc")]
+mod distance_field;
+#[cfg(feature = "document")]
+pub mod document;
+#[cfg(feature = "alloc")]
+pub mod fill;
+pub mod flatten_options;
+pub mod forward_differences;
+#[cfg(feature = "alloc")]
+pub mod joining;
+pub mod line_to;
+pub mod math;
+#[cfg(feature = "alloc")]
+pub mod ordering;
+#[cfg(feature = "svgtypes")]
+pub mod path_error;
+#[cfg(feature = "svgtypes")]
+pub mod placement;
 pub mod point;
+pub mod primitive;
+#[cfg(feature = "svgtypes")]
+mod segment_resolver;
+#[cfg(feature = "svgtypes")]
+pub mod shapes;
+#[cfg(feature = "alloc")]
+pub mod sub_path;
 pub mod svg_curve;
 mod tick_timer;
+pub mod transform;
@@ -0,0 +1,460 @@
+//! Reordering sub-paths so the pen spends less time in the air between them.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::line_to::LineTo;
+use super::math::distance;
+use super::point::Point;
+use super::sub_path::{line_tos_from_sub_paths, sub_paths, PointGrid, SubPath};
+use crate::float::sqrt;
+
+// changes that save less than this are not worth another pass
+const MIN_GAIN: f64 = 1e-9;
+const MAX_TWO_OPT_PASSES: usize = 64;
+const MAX_ROUNDS: usize = 4;
+// loops whose bounds cover more grid cells than this are checked at every step instead
+const MAX_LOOP_CELLS: i64 = 64;
+
+/// Total length of the `Fly` moves before and after reordering.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrderReport {
+    pub pen_up_before: f64,
+    pub pen_up_after: f64,
+}
+
+/// Length of the `Fly` moves needed to draw `sub_paths` in order, starting at `start`.
+pub fn pen_up_distance(sub_paths: &[SubPath], start: Point) -> f64 {
+    let mut pen = start;
+    let mut total = 0.;
+    for sub_path in sub_paths {
+        if let (Some(first), Some(last)) = (sub_path.points.first(), sub_path.points.last()) {
+            total += distance(pen, *first);
+            pen = *last;
+        }
+    }
+    total
+}
+
+/// Puts `sub_paths` in an order with less pen-up travel for a pen starting at `start`:
+/// a nearest-neighbour tour improved by 2-opt. Open sub-paths may be drawn backwards
+/// and closed ones may start at any of their points; what gets drawn stays the same.
+/// The input order is kept if nothing shorter is found.
+pub fn order_sub_paths(sub_paths: Vec<SubPath>, start: Point) -> (Vec<SubPath>, OrderReport) {
+    let pen_up_before = pen_up_distance(&sub_paths, start);
+    let mut tour = nearest_neighbour_tour(&sub_paths, start);
+    for _ in 0..MAX_ROUNDS {
+        let reversed = two_opt(&mut tour, start);
+        let rotated = choose_loop_starts(&sub_paths, &mut tour, start);
+        if !(reversed || rotated) {
+            break;
+        }
+    }
+
+    let pen_up_after = tour_length(&tour, start);
+    if pen_up_after >= pen_up_before {
+        let report = OrderReport {
+            pen_up_before,
+            pen_up_after: pen_up_before,
+        };
+        return (sub_paths, report);
+    }
+
+    let mut sub_paths: Vec<Option<SubPath>> = sub_paths.into_iter().map(Some).collect();
+    let ordered = tour
+        .iter()
+        .map(|stop| {
+            let mut sub_path = sub_paths[stop.path].take().expect("every sub-path once");
+            sub_path.rotate_start(stop.start);
+            if stop.reversed {
+                sub_path.reverse();
+            }
+            sub_path
+        })
+        .collect();
+    let report = OrderReport {
+        pen_up_before,
+        pen_up_after,
+    };
+    (ordered, report)
+}
+
+/// `order_sub_paths` for a flattened stream, cut up and put back together as described
+/// in `sub_path`.
+pub fn order_line_tos(
+    line_tos: impl IntoIterator<Item = LineTo>,
+    start: Point,
+) -> (Vec<LineTo>, OrderReport) {
+    let (sub_paths, report) = order_sub_paths(sub_paths(line_tos), start);
+    (line_tos_from_sub_paths(&sub_paths), report)
+}
+
+// === private members ===
+
+/// A sub-path in the tour. `entry` and `exit` are where the pen goes down and up.
+#[derive(Debug, Copy, Clone)]
+struct Stop {
+    path: usize,
+    /// The point a closed sub-path starts at, 0 for open ones.
+    start: usize,
+    reversed: bool,
+    entry: Point,
+    exit: Point,
+}
+
+impl Stop {
+    fn reverse(&mut self) {
+        self.reversed = !self.reversed;
+        core::mem::swap(&mut self.entry, &mut self.exit);
+    }
+}
+
+// every way of drawing the sub-path: both directions if it is open, every start point
+// if it is closed (the direction of a loop does not change where it starts)
+fn stops(path: usize, sub_path: &SubPath) -> impl Iterator<Item = Stop> + '_ {
+    let points = &sub_path.points;
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (open, starts) = match sub_path.is_closed() {
+        true => (0, points.len() - 1),
+        false => (2, 0),
+    };
+    let open_stops = (0..open).map(move |reversed| Stop {
+        path,
+        start: 0,
+        reversed: reversed == 1,
+        entry: if reversed == 1 { last } else { first },
+        exit: if reversed == 1 { first } else { last },
+    });
+    let loop_stops = (0..starts).map(move |start| Stop {
+        path,
+        start,
+        reversed: false,
+        entry: points[start],
+        exit: points[start],
+    });
+    open_stops.chain(loop_stops)
+}
+
+// each step goes to the sub-path with the nearest end or, for loops, the nearest
+// bounds, and enters it at its point nearest the pen; `choose_loop_starts` looks at the
+// loop starts again once the order is settled
+fn nearest_neighbour_tour(sub_paths: &[SubPath], start: Point) -> Vec<Stop> {
+    let targets: Vec<Option<Target>> = sub_paths.iter().map(Target::of).collect();
+    let candidates = Candidates::new(&targets);
+    let mut visited: Vec<bool> = targets.iter().map(Option::is_none).collect();
+    let mut tour = Vec::with_capacity(sub_paths.len());
+    let mut pen = start;
+    while let Some(path) = candidates.nearest(pen, &targets, &visited) {
+        visited[path] = true;
+        let stop = stops(path, &sub_paths[path])
+            .map(|stop| (distance(pen, stop.entry), stop))
+            .fold(None, |best: Option<(f64, Stop)>, candidate| match best {
+                Some(best) if best.0 <= candidate.0 => Some(best),
+                _ => Some(candidate),
+            })
+            .expect("sub-paths with points have stops")
+            .1;
+        pen = stop.exit;
+        tour.push(stop);
+    }
+    tour
+}
+
+/// Where the pen can go down on a sub-path, as far as picking the next one goes.
+#[derive(Debug, Copy, Clone)]
+enum Target {
+    Ends(Point, Point),
+    /// Lowest and highest corner of the bounds of a loop.
+    Bounds(Point, Point),
+}
+
+impl Target {
+    fn of(sub_path: &SubPath) -> Option<Target> {
+        let points = &sub_path.points;
+        let (first, last) = (*points.first()?, *points.last()?);
+        if !sub_path.is_closed() {
+            return Some(Target::Ends(first, last));
+        }
+        let (low, high) = points.iter().fold((first, first), |(low, high), point| {
+            (
+                Point::new(low.x.min(point.x), low.y.min(point.y)),
+                Point::new(high.x.max(point.x), high.y.max(point.y)),
+            )
+        });
+        Some(Target::Bounds(low, high))
+    }
+
+    fn distance(&self, pen: Point) -> f64 {
+        match *self {
+            Target::Ends(first, last) => distance(pen, first).min(distance(pen, last)),
+            Target::Bounds(low, high) => {
+                let x = (low.x - pen.x).max(pen.x - high.x).max(0.);
+                let y = (low.y - pen.y).max(pen.y - high.y).max(0.);
+                sqrt(x * x + y * y)
+            }
+        }
+    }
+}
+
+/// Sub-paths bucketed by the cells their ends or bounds are in, searched in growing
+/// squares of cells round the pen.
+struct Candidates {
+    grid: PointGrid,
+    cells: BTreeMap<(i64, i64), Vec<usize>>,
+    /// Loops too big to put in every cell they cover, checked every time.
+    large: Vec<usize>,
+    /// Lowest and highest cell with anything in it.
+    low: (i64, i64),
+    high: (i64, i64),
+}
+
+impl Candidates {
+    // cells about big enough to hold one sub-path each
+    fn new(targets: &[Option<Target>]) -> Self {
+        let corners = |target: &Target| match *target {
+            Target::Ends(first, last) => [first, last],
+            Target::Bounds(low, high) => [low, high],
+        };
+        let bounds = targets.iter().flatten().flat_map(corners).fold(
+            None,
+            |bounds: Option<(Point, Point)>, point| {
+                let (low, high) = bounds.unwrap_or((point, point));
+                Some((
+                    Point::new(low.x.min(point.x), low.y.min(point.y)),
+                    Point::new(high.x.max(point.x), high.y.max(point.y)),
+                ))
+            },
+        );
+        let count = targets.len().max(1) as f64;
+        let cell = bounds.map_or(0., |(low, high)| {
+            let (width, height) = (high.x - low.x, high.y - low.y);
+            sqrt(width * height / count).max(width.max(height) / count)
+        });
+
+        let mut candidates = Candidates {
+            grid: PointGrid::new(cell),
+            cells: BTreeMap::new(),
+            large: Vec::new(),
+            low: (i64::MAX, i64::MAX),
+            high: (i64::MIN, i64::MIN),
+        };
+        for (path, target) in targets.iter().enumerate() {
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+            let [low, high] = corners(target).map(|point| candidates.grid.key(point));
+            match target {
+                Target::Ends(..) => {
+                    candidates.add(low, path);
+                    if high != low {
+                        candidates.add(high, path);
+                    }
+                }
+                Target::Bounds(..) => {
+                    let columns = high.0.saturating_sub(low.0).saturating_add(1);
+                    let rows = high.1.saturating_sub(low.1).saturating_add(1);
+                    if columns.saturating_mul(rows) > MAX_LOOP_CELLS {
+                        candidates.large.push(path);
+                        continue;
+                    }
+                    for row in low.1..=high.1 {
+                        for column in low.0..=high.0 {
+                            candidates.add((column, row), path);
+                        }
+                    }
+                }
+            }
+        }
+        candidates
+    }
+
+    fn add(&mut self, key: (i64, i64), path: usize) {
+        self.cells.entry(key).or_default().push(path);
+        self.low = (self.low.0.min(key.0), self.low.1.min(key.1));
+        self.high = (self.high.0.max(key.0), self.high.1.max(key.1));
+    }
+
+    // the sub-paths with an end or bounds in the cells around `point`, some of them more
+    // than once
+    fn around(&self, point: Point) -> impl Iterator<Item = usize> + '_ {
+        self.grid
+            .around(point)
+            .filter_map(move |key| self.cells.get(&key))
+            .flatten()
+            .chain(self.large.iter())
+            .copied()
+    }
+
+    // the unvisited sub-path nearest to `pen`, the first one of those equally near
+    fn nearest(&self, pen: Point, targets: &[Option<Target>], visited: &[bool]) -> Option<usize> {
+        let mut best: Option<(f64, usize)> = None;
+        let consider = |best: &mut Option<(f64, usize)>, path: usize| {
+            if visited[path] {
+                return;
+            }
+            let distance = targets[path].map_or(f64::INFINITY, |target| target.distance(pen));
+            match best {
+                Some(nearest) if *nearest <= (distance, path) => {}
+                _ => *best = Some((distance, path)),
+            }
+        };
+        for path in &self.large {
+            consider(&mut best, *path);
+        }
+        if self.cells.is_empty() {
+            return best.map(|(_, path)| path);
+        }
+
+        // squares of cells at `ring` from the pen's cell, as far out as there are cells,
+        // until the nearest one found is closer than anything further out can be
+        let centre = self.grid.key(pen);
+        let gap =
+            |low: i64, high: i64, at: i64| low.saturating_sub(at).max(at.saturating_sub(high));
+        let first = gap(self.low.0, self.high.0, centre.0)
+            .max(gap(self.low.1, self.high.1, centre.1))
+            .max(0);
+        let last = [
+            centre.0.saturating_sub(self.low.0),
+            self.high.0.saturating_sub(centre.0),
+            centre.1.saturating_sub(self.low.1),
+            self.high.1.saturating_sub(centre.1),
+        ]
+        .iter()
+        .fold(0, |last, reach| last.max(*reach));
+        for ring in first..=last {
+            if let Some((distance, _)) = best {
+                if distance < (ring - 1) as f64 * self.grid.cell {
+                    break;
+                }
+            }
+            let (left, right) = (centre.0.saturating_sub(ring), centre.0.saturating_add(ring));
+            let (top, bottom) = (centre.1.saturating_sub(ring), centre.1.saturating_add(ring));
+            for row in top.max(self.low.1)..=bottom.min(self.high.1) {
+                let mut visit = |column: i64| {
+                    if let Some(paths) = self.cells.get(&(column, row)) {
+                        for path in paths {
+                            consider(&mut best, *path);
+                        }
+                    }
+                };
+                if row == top || row == bottom {
+                    (left.max(self.low.0)..=right.min(self.high.0)).for_each(&mut visit);
+                } else {
+                    visit(left);
+                    if right != left {
+                        visit(right);
+                    }
+                }
+            }
+        }
+        best.map(|(_, path)| path)
+    }
+}
+
+fn tour_length(tour: &[Stop], start: Point) -> f64 {
+    let mut pen = start;
+    let mut total = 0.;
+    for stop in tour {
+        total += distance(pen, stop.entry);
+        pen = stop.exit;
+    }
+    total
+}
+
+// reverses runs of the tour, and with them the direction of every stop in the run,
+// while that shortens the pen-up moves at the ends of the run. Only runs that end at a
+// stop with an end near the pen before the run are tried, found in a grid of the stop
+// ends rebuilt every pass; reversing runs only swaps the ends of their stops.
+fn two_opt(tour: &mut [Stop], start: Point) -> bool {
+    let paths = tour.iter().map(|stop| stop.path + 1).max().unwrap_or(0);
+    let mut improved_any = false;
+    for _ in 0..MAX_TWO_OPT_PASSES {
+        let mut targets = vec![None; paths];
+        let mut positions = vec![0; paths];
+        for (index, stop) in tour.iter().enumerate() {
+            targets[stop.path] = Some(Target::Ends(stop.entry, stop.exit));
+            positions[stop.path] = index;
+        }
+        let candidates = Candidates::new(&targets);
+
+        let mut improved = false;
+        for first in 0..tour.len() {
+            let before = if first == 0 {
+                start
+            } else {
+                tour[first - 1].exit
+            };
+            let mut lasts: Vec<usize> = candidates
+                .around(before)
+                .map(|path| positions[path])
+                .filter(|last| *last >= first)
+                .collect();
+            lasts.sort_unstable();
+            lasts.dedup();
+            for last in lasts {
+                let mut gain =
+                    distance(before, tour[first].entry) - distance(before, tour[last].exit);
+                if let Some(after) = tour.get(last + 1) {
+                    gain += distance(tour[last].exit, after.entry)
+                        - distance(tour[first].entry, after.entry);
+                }
+                if gain > MIN_GAIN {
+                    tour[first..=last].reverse();
+                    for (index, stop) in tour[first..=last].iter_mut().enumerate() {
+                        stop.reverse();
+                        positions[stop.path] = first + index;
+                    }
+                    improved = true;
+                    // the other candidates moved, they get their turn next pass
+                    break;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+        improved_any = true;
+    }
+    improved_any
+}
+
+// moves the start of every loop to the point closest to its neighbours in the tour
+fn choose_loop_starts(sub_paths: &[SubPath], tour: &mut [Stop], start: Point) -> bool {
+    let mut improved = false;
+    for index in 0..tour.len() {
+        let sub_path = &sub_paths[tour[index].path];
+        if !sub_path.is_closed() {
+            continue;
+        }
+        let before = if index == 0 {
+            start
+        } else {
+            tour[index - 1].exit
+        };
+        let after = tour.get(index + 1).map(|stop| stop.entry);
+        let cost = |point: Point| {
+            distance(before, point) + after.map_or(0., |after| distance(point, after))
+        };
+
+        let current = cost(tour[index].entry);
+        let best = (0..sub_path.points.len() - 1)
+            .map(|start| (cost(sub_path.points[start]), start))
+            .fold((current, None), |best, (cost, start)| {
+                if cost < best.0 - MIN_GAIN {
+                    (cost, Some(start))
+                } else {
+                    best
+                }
+            });
+        if let (_, Some(start)) = best {
+            let stop = &mut tour[index];
+            stop.start = start;
+            stop.entry = sub_path.points[start];
+            stop.exit = sub_path.points[start];
+            improved = true;
+        }
+    }
+    improved
+}
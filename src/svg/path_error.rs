@@ -0,0 +1,159 @@
+//! Errors for path data that must not reach the motors: unparsable `d` strings and
+//! numbers that are not finite.
+
+use core::fmt;
+use svgtypes::PathSegment;
+
+use super::primitive::Primitive;
+
+/// Why flattening stopped, and at which source segment.
+#[derive(Debug)]
+pub struct PathError {
+    /// Index of the offending segment in the input, counting from 0. For a parse
+    /// error this is the number of segments parsed before it.
+    pub segment: usize,
+    pub kind: PathErrorKind,
+}
+
+#[derive(Debug)]
+pub enum PathErrorKind {
+    /// The path data could not be parsed. `position` is the byte offset into the
+    /// path data where parsing failed, when the parser reports one.
+    Parse {
+        error: svgtypes::Error,
+        position: Option<usize>,
+    },
+    /// A coordinate or arc rotation is NaN or infinite, as given or once resolved
+    /// and transformed.
+    NonFiniteCoordinate,
+    /// An arc radius is NaN or infinite.
+    NonFiniteRadius,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "segment {}: ", self.segment)?;
+        match &self.kind {
+            PathErrorKind::Parse {
+                error,
+                position: Some(position),
+            } => write!(f, "{} (byte {})", error, position),
+            PathErrorKind::Parse {
+                error,
+                position: None,
+            } => write!(f, "{}", error),
+            PathErrorKind::NonFiniteCoordinate => write!(f, "coordinate is not finite"),
+            PathErrorKind::NonFiniteRadius => write!(f, "arc radius is not finite"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PathError {}
+
+impl From<svgtypes::Error> for PathErrorKind {
+    fn from(error: svgtypes::Error) -> Self {
+        // svgtypes counts characters from 1. Path data is ASCII and parsing stops at the
+        // first character that is not, so up to the error characters are bytes.
+        let position = match error {
+            svgtypes::Error::UnexpectedData(position)
+            | svgtypes::Error::InvalidChar(_, position)
+            | svgtypes::Error::InvalidString(_, position)
+            | svgtypes::Error::InvalidNumber(position) => Some(position.saturating_sub(1)),
+            _ => None,
+        };
+        PathErrorKind::Parse { error, position }
+    }
+}
+
+/// Checks the numbers of a segment as written in the path data.
+pub(crate) fn check_segment(segment: &PathSegment) -> Result<(), PathErrorKind> {
+    let coordinates_finite = match *segment {
+        PathSegment::MoveTo { x, y, .. }
+        | PathSegment::LineTo { x, y, .. }
+        | PathSegment::SmoothQuadratic { x, y, .. } => all_finite(&[x, y]),
+        PathSegment::HorizontalLineTo { x, .. } => x.is_finite(),
+        PathSegment::VerticalLineTo { y, .. } => y.is_finite(),
+        PathSegment::CurveTo {
+            x1,
+            y1,
+            x2,
+            y2,
+            x,
+            y,
+            ..
+        } => all_finite(&[x1, y1, x2, y2, x, y]),
+        PathSegment::SmoothCurveTo { x2, y2, x, y, .. } => all_finite(&[x2, y2, x, y]),
+        PathSegment::Quadratic { x1, y1, x, y, .. } => all_finite(&[x1, y1, x, y]),
+        PathSegment::EllipticalArc {
+            rx,
+            ry,
+            x_axis_rotation,
+            x,
+            y,
+            ..
+        } => {
+            if !all_finite(&[rx, ry]) {
+                return Err(PathErrorKind::NonFiniteRadius);
+            }
+            all_finite(&[x_axis_rotation, x, y])
+        }
+        PathSegment::ClosePath { .. } => true,
+    };
+    if coordinates_finite {
+        Ok(())
+    } else {
+        Err(PathErrorKind::NonFiniteCoordinate)
+    }
+}
+
+/// Checks resolved geometry, which can overflow even when every number in the path
+/// data is finite, e.g. through long chains of relative moves or a transform.
+pub(crate) fn check_primitive(primitive: &Primitive) -> Result<(), PathErrorKind> {
+    let coordinates_finite = match *primitive {
+        Primitive::Move { end } => all_finite(&[end.x, end.y]),
+        Primitive::Line { start, end } => all_finite(&[start.x, start.y, end.x, end.y]),
+        Primitive::Quadratic { start, p1, end } => {
+            all_finite(&[start.x, start.y, p1.x, p1.y, end.x, end.y])
+        }
+        Primitive::Cubic { start, p1, p2, end } => {
+            all_finite(&[start.x, start.y, p1.x, p1.y, p2.x, p2.y, end.x, end.y])
+        }
+        Primitive::Arc {
+            start,
+            end,
+            center,
+            rx,
+            ry,
+            x_rotation,
+            start_angle,
+            sweep_angle,
+        } => {
+            if !all_finite(&[rx, ry]) {
+                return Err(PathErrorKind::NonFiniteRadius);
+            }
+            all_finite(&[
+                start.x,
+                start.y,
+                end.x,
+                end.y,
+                center.x,
+                center.y,
+                x_rotation,
+                start_angle,
+                sweep_angle,
+            ])
+        }
+    };
+    if coordinates_finite {
+        Ok(())
+    } else {
+        Err(PathErrorKind::NonFiniteCoordinate)
+    }
+}
+
+// === private members ===
+
+fn all_finite(values: &[f64]) -> bool {
+    values.iter().all(|value| value.is_finite())
+}
@@ -0,0 +1,381 @@
+//! Mapping SVG user units onto the drawing board. Everything on the board side is in
+//! millimetres.
+
+use svgtypes::{Align as AspectAlign, AspectRatio, Length, LengthUnit, ViewBox};
+
+use super::line_to::{ArcChords, CircularArc, LineTo};
+use super::point::Point;
+use super::transform::Transform;
+use crate::float::sqrt;
+
+/// CSS pixels are 1/96 inch, and SVG user units are CSS pixels.
+pub const MM_PER_PX: f64 = 25.4 / 96.;
+
+/// A length in CSS pixels, `None` for percentages, which need a reference size.
+/// `em` and `ex` assume the usual 16px font.
+pub fn length_in_px(length: Length) -> Option<f64> {
+    let px_per_unit = match length.unit {
+        LengthUnit::None | LengthUnit::Px => 1.,
+        LengthUnit::In => 96.,
+        LengthUnit::Cm => 96. / 2.54,
+        LengthUnit::Mm => 96. / 25.4,
+        LengthUnit::Pt => 96. / 72.,
+        LengthUnit::Pc => 16.,
+        LengthUnit::Em => 16.,
+        LengthUnit::Ex => 8.,
+        LengthUnit::Percent => return None,
+    };
+    Some(length.num * px_per_unit)
+}
+
+pub fn length_in_mm(length: Length) -> Option<f64> {
+    length_in_px(length).map(|px| px * MM_PER_PX)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Smallest rectangle around everything the moves can reach, arcs included as
+    /// their full circle. `None` for an empty stream.
+    pub fn bounding<'a>(line_tos: impl IntoIterator<Item = &'a LineTo>) -> Option<Rect> {
+        let mut bounds: Option<(Point, Point)> = None;
+        let mut include = |point: Point| {
+            bounds = Some(match bounds {
+                None => (point, point),
+                Some((min, max)) => (
+                    Point::new(min.x.min(point.x), min.y.min(point.y)),
+                    Point::new(max.x.max(point.x), max.y.max(point.y)),
+                ),
+            })
+        };
+        for line_to in line_tos {
+            if let LineTo::Arc(arc) = line_to {
+                include(arc.center - arc.radius);
+                include(arc.center + arc.radius);
+            }
+            include(line_to.target());
+        }
+        bounds.map(|(min, max)| Rect::new(min.x, min.y, max.x - min.x, max.y - min.y))
+    }
+
+    /// Whether `point` is inside or on the border.
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.x
+            && point.x <= self.x + self.width
+            && point.y >= self.y
+            && point.y <= self.y + self.height
+    }
+
+    /// The part of both rectangles, with zero size where they do not meet.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        Rect::new(x, y, (right - x).max(0.), (bottom - y).max(0.))
+    }
+}
+
+/// The transform from a `viewBox` to the `viewport` it is shown in, following the
+/// `preserveAspectRatio` rules.
+pub fn view_box_transform(view_box: ViewBox, aspect: AspectRatio, viewport: Rect) -> Transform {
+    let mut scale_x = viewport.width / view_box.w;
+    let mut scale_y = viewport.height / view_box.h;
+    if aspect.align != AspectAlign::None {
+        let scale = if aspect.slice {
+            scale_x.max(scale_y)
+        } else {
+            scale_x.min(scale_y)
+        };
+        scale_x = scale;
+        scale_y = scale;
+    }
+
+    let (align_x, align_y) = match aspect.align {
+        AspectAlign::None | AspectAlign::XMinYMin => (Align::Start, Align::Start),
+        AspectAlign::XMidYMin => (Align::Center, Align::Start),
+        AspectAlign::XMaxYMin => (Align::End, Align::Start),
+        AspectAlign::XMinYMid => (Align::Start, Align::Center),
+        AspectAlign::XMidYMid => (Align::Center, Align::Center),
+        AspectAlign::XMaxYMid => (Align::End, Align::Center),
+        AspectAlign::XMinYMax => (Align::Start, Align::End),
+        AspectAlign::XMidYMax => (Align::Center, Align::End),
+        AspectAlign::XMaxYMax => (Align::End, Align::End),
+    };
+    let translate_x =
+        viewport.x - view_box.x * scale_x + align_x.offset(viewport.width - view_box.w * scale_x);
+    let translate_y =
+        viewport.y - view_box.y * scale_y + align_y.offset(viewport.height - view_box.h * scale_y);
+    Transform::new(scale_x, 0., 0., scale_y, translate_x, translate_y)
+}
+
+/// Where the drawing goes inside the space left over along one axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    fn offset(self, free_space: f64) -> f64 {
+        match self {
+            Align::Start => 0.,
+            Align::Center => free_space / 2.,
+            Align::End => free_space,
+        }
+    }
+}
+
+/// How the drawing is scaled into the target rectangle; it always keeps its aspect ratio.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Fit {
+    /// Keep the physical size, even if the drawing does not fit.
+    ActualSize,
+    /// Scale up or down until the drawing touches the target on two sides.
+    Contain,
+    /// Like `Contain`, but never enlarge.
+    ShrinkToFit,
+}
+
+/// A rectangle on the board the drawing is placed into.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Placement {
+    pub target: Rect,
+    /// Kept free on every side of the target.
+    pub margin: f64,
+    pub fit: Fit,
+    pub align_x: Align,
+    pub align_y: Align,
+}
+
+impl Placement {
+    /// Fills `target`, centred, without margins.
+    pub fn new(target: Rect) -> Self {
+        Placement {
+            target,
+            margin: 0.,
+            fit: Fit::Contain,
+            align_x: Align::Center,
+            align_y: Align::Center,
+        }
+    }
+
+    /// The target less its margins, where drawing is allowed.
+    pub fn area(&self) -> Rect {
+        Rect::new(
+            self.target.x + self.margin,
+            self.target.y + self.margin,
+            (self.target.width - 2. * self.margin).max(0.),
+            (self.target.height - 2. * self.margin).max(0.),
+        )
+    }
+
+    /// The transform that moves `drawing` (in millimetres) to its place on the board.
+    pub fn transform(&self, drawing: Rect) -> Transform {
+        let Rect {
+            x: area_x,
+            y: area_y,
+            width,
+            height,
+        } = self.area();
+
+        let fill = match (drawing.width > 0., drawing.height > 0.) {
+            (true, true) => (width / drawing.width).min(height / drawing.height),
+            (true, false) => width / drawing.width,
+            (false, true) => height / drawing.height,
+            (false, false) => 1.,
+        };
+        let scale = match self.fit {
+            Fit::ActualSize => 1.,
+            Fit::Contain => fill,
+            Fit::ShrinkToFit => fill.min(1.),
+        };
+
+        let x = area_x - drawing.x * scale + self.align_x.offset(width - drawing.width * scale);
+        let y = area_y - drawing.y * scale + self.align_y.offset(height - drawing.height * scale);
+        Transform::new(scale, 0., 0., scale, x, y)
+    }
+
+    /// Places an already flattened stream whose points are in millimetres, fitting
+    /// `drawing` into the target. See `Rect::bounding` for fitting the content itself.
+    /// Whatever ends up outside `area`, as with `Fit::ActualSize`, is cut off.
+    pub fn apply(
+        &self,
+        drawing: Rect,
+        line_tos: impl Iterator<Item = LineTo>,
+    ) -> impl Iterator<Item = LineTo> {
+        let transform = self.transform(drawing);
+        clip_line_tos(
+            line_tos.map(move |line_to| transform_line_to(line_to, &transform)),
+            self.area(),
+        )
+    }
+}
+
+/// Cuts away everything outside `rect`. Drawing moves that cross its border stop there
+/// and the pen flies to where the next one comes back in; flights outside are dropped.
+/// Arcs whose whole circle fits are kept, the others are cut into chords at most
+/// 2 degrees apart first.
+pub fn clip_line_tos(
+    line_tos: impl Iterator<Item = LineTo>,
+    rect: Rect,
+) -> impl Iterator<Item = LineTo> {
+    ClipIterator {
+        line_tos,
+        rect,
+        pen: Point::ZERO,
+        output_pen: None,
+        chords: None,
+        pending: None,
+    }
+}
+
+// exact for the uniform scales and moves `Placement` makes: circles stay circles
+pub(crate) fn transform_line_to(line_to: LineTo, transform: &Transform) -> LineTo {
+    match line_to {
+        LineTo::Fly(point) => LineTo::Fly(transform.apply(point)),
+        LineTo::Draw(point) => LineTo::Draw(transform.apply(point)),
+        LineTo::Erase(point) => LineTo::Erase(transform.apply(point)),
+        LineTo::Arc(arc) => {
+            let determinant = transform.a * transform.d - transform.b * transform.c;
+            LineTo::Arc(CircularArc {
+                end: transform.apply(arc.end),
+                center: transform.apply(arc.center),
+                radius: arc.radius * sqrt(determinant.abs()),
+                clockwise: arc.clockwise == (determinant > 0.),
+            })
+        }
+    }
+}
+
+struct ClipIterator<I> {
+    line_tos: I,
+    rect: Rect,
+    // where the pen of the input is
+    pen: Point,
+    // where the pen of the output is, `None` before it was moved
+    output_pen: Option<Point>,
+    chords: Option<ArcChords>,
+    // a drawing move waiting for the `Fly` to its start to be taken
+    pending: Option<LineTo>,
+}
+
+impl<I: Iterator<Item = LineTo>> Iterator for ClipIterator<I> {
+    type Item = LineTo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(line_to) = self.pending.take() {
+            return Some(line_to);
+        }
+        loop {
+            let line_to = match self.chords.as_mut().and_then(Iterator::next) {
+                Some(point) => LineTo::Draw(point),
+                None => {
+                    self.chords = None;
+                    self.line_tos.next()?
+                }
+            };
+            let start = self.pen;
+            self.pen = line_to.target();
+            match line_to {
+                LineTo::Fly(point) => {
+                    if self.rect.contains(point) && self.output_pen != Some(point) {
+                        self.output_pen = Some(point);
+                        return Some(line_to);
+                    }
+                }
+                LineTo::Draw(end) | LineTo::Erase(end) => {
+                    if let Some((from, to)) = clip_segment(&self.rect, start, end) {
+                        let cut = match line_to {
+                            LineTo::Erase(_) => LineTo::Erase(to),
+                            _ => LineTo::Draw(to),
+                        };
+                        return Some(self.drawn_from(from, cut));
+                    }
+                }
+                LineTo::Arc(arc) => {
+                    let circle = Rect::new(
+                        arc.center.x - arc.radius,
+                        arc.center.y - arc.radius,
+                        2. * arc.radius,
+                        2. * arc.radius,
+                    );
+                    if self.rect.intersection(&circle) == circle {
+                        return Some(self.drawn_from(start, line_to));
+                    }
+                    self.pen = start;
+                    self.chords = Some(ArcChords::new(start, &arc));
+                }
+            }
+        }
+    }
+}
+
+impl<I> ClipIterator<I> {
+    // `line_to` drawn from `start`, after a `Fly` there if the output pen is elsewhere
+    fn drawn_from(&mut self, start: Point, line_to: LineTo) -> LineTo {
+        let moved_from = self.output_pen.replace(line_to.target());
+        if moved_from == Some(start) {
+            line_to
+        } else {
+            self.pending = Some(line_to);
+            LineTo::Fly(start)
+        }
+    }
+}
+
+// the part of the segment inside `rect`, after Liang and Barsky; a segment that only
+// touches the border at a single point is left out
+fn clip_segment(rect: &Rect, start: Point, end: Point) -> Option<(Point, Point)> {
+    let delta = end - start;
+    let mut enter: f64 = 0.;
+    let mut exit: f64 = 1.;
+    // every side as `step * t <= room`
+    let sides = [
+        (-delta.x, start.x - rect.x),
+        (delta.x, rect.x + rect.width - start.x),
+        (-delta.y, start.y - rect.y),
+        (delta.y, rect.y + rect.height - start.y),
+    ];
+    for (step, room) in sides {
+        if step == 0. {
+            if room < 0. {
+                return None;
+            }
+        } else if step < 0. {
+            enter = enter.max(room / step);
+        } else {
+            exit = exit.min(room / step);
+        }
+    }
+    if enter > exit || (enter == exit && start != end) {
+        return None;
+    }
+    // cut points are kept from straying outside by rounding
+    let cut = |time: f64| {
+        let point = start + delta * time;
+        Point::new(
+            point.x.clamp(rect.x, rect.x + rect.width),
+            point.y.clamp(rect.y, rect.y + rect.height),
+        )
+    };
+    let from = if enter > 0. { cut(enter) } else { start };
+    let to = if exit < 1. { cut(exit) } else { end };
+    Some((from, to))
+}
@@ -1,23 +1,32 @@
+use crate::scalar::Scalar;
 use core::ops::{Add, Div, Mul, Sub};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Point {
-    pub x: f64,
-    pub y: f64,
+pub struct Point<S: Scalar = f64> {
+    pub x: S,
+    pub y: S,
 }
 
-impl Point {
-    pub fn new(x: f64, y: f64) -> Self {
+impl<S: Scalar> Point<S> {
+    pub fn new(x: S, y: S) -> Self {
         Point { x, y }
     }
 
-    pub const ZERO: Point = Point { x: 0., y: 0. };
+    pub const ZERO: Point<S> = Point {
+        x: S::ZERO,
+        y: S::ZERO,
+    };
+
+    /// Same point with coordinates in another scalar type.
+    pub fn cast<T: Scalar>(self) -> Point<T> {
+        Point::new(T::from_f64(self.x.to_f64()), T::from_f64(self.y.to_f64()))
+    }
 }
 
-impl Div<f64> for Point {
-    type Output = Point;
+impl<S: Scalar> Div<S> for Point<S> {
+    type Output = Point<S>;
 
-    fn div(self, rhs: f64) -> Self::Output {
+    fn div(self, rhs: S) -> Self::Output {
         Point {
             x: self.x / rhs,
             y: self.y / rhs,
@@ -25,10 +34,10 @@ impl Div<f64> for Point {
     }
 }
 
-impl Mul<f64> for Point {
-    type Output = Point;
+impl<S: Scalar> Mul<S> for Point<S> {
+    type Output = Point<S>;
 
-    fn mul(self, rhs: f64) -> Self::Output {
+    fn mul(self, rhs: S) -> Self::Output {
         Point {
             x: self.x * rhs,
             y: self.y * rhs,
@@ -36,10 +45,10 @@ impl Mul<f64> for Point {
     }
 }
 
-impl Add<f64> for Point {
-    type Output = Point;
+impl<S: Scalar> Add<S> for Point<S> {
+    type Output = Point<S>;
 
-    fn add(self, rhs: f64) -> Self::Output {
+    fn add(self, rhs: S) -> Self::Output {
         Point {
             x: self.x + rhs,
             y: self.y + rhs,
@@ -47,10 +56,10 @@ impl Add<f64> for Point {
     }
 }
 
-impl Add<Point> for Point {
-    type Output = Point;
+impl<S: Scalar> Add<Point<S>> for Point<S> {
+    type Output = Point<S>;
 
-    fn add(self, rhs: Point) -> Self::Output {
+    fn add(self, rhs: Point<S>) -> Self::Output {
         Point {
             x: self.x + rhs.x,
             y: self.y + rhs.y,
@@ -58,10 +67,10 @@ impl Add<Point> for Point {
     }
 }
 
-impl Sub<Point> for Point {
-    type Output = Point;
+impl<S: Scalar> Sub<Point<S>> for Point<S> {
+    type Output = Point<S>;
 
-    fn sub(self, rhs: Point) -> Self::Output {
+    fn sub(self, rhs: Point<S>) -> Self::Output {
         Point {
             x: self.x - rhs.x,
             y: self.y - rhs.y,
@@ -69,10 +78,10 @@ impl Sub<Point> for Point {
     }
 }
 
-impl Sub<f64> for Point {
-    type Output = Point;
+impl<S: Scalar> Sub<S> for Point<S> {
+    type Output = Point<S>;
 
-    fn sub(self, rhs: f64) -> Self::Output {
+    fn sub(self, rhs: S) -> Self::Output {
         Point {
             x: self.x - rhs,
             y: self.y - rhs,
@@ -0,0 +1,111 @@
+use super::point::*;
+use super::transform::Transform;
+
+#[cfg(feature = "svgtypes")]
+pub use super::segment_resolver::primitives_from_path_segments;
+
+/// Absolute geometry of a single path segment: relative coordinates are resolved,
+/// smooth control points are mirrored and arcs are converted to center parameterization.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Primitive {
+    Move {
+        end: Point,
+    },
+    Line {
+        start: Point,
+        end: Point,
+    },
+    Quadratic {
+        start: Point,
+        p1: Point,
+        end: Point,
+    },
+    Cubic {
+        start: Point,
+        p1: Point,
+        p2: Point,
+        end: Point,
+    },
+    /// Elliptical arc; angles are in radians and `x_rotation` turns the `rx` axis
+    /// away from the x axis.
+    Arc {
+        start: Point,
+        end: Point,
+        center: Point,
+        rx: f64,
+        ry: f64,
+        x_rotation: f64,
+        start_angle: f64,
+        sweep_angle: f64,
+    },
+}
+
+impl Primitive {
+    pub fn end(&self) -> Point {
+        match *self {
+            Primitive::Move { end } => end,
+            Primitive::Line { end, .. } => end,
+            Primitive::Quadratic { end, .. } => end,
+            Primitive::Cubic { end, .. } => end,
+            Primitive::Arc { end, .. } => end,
+        }
+    }
+
+    /// The same primitive seen through `transform`. Bezier curves only need their
+    /// control points moved; arcs get new radii, rotation and angles.
+    pub fn transformed(self, transform: &Transform) -> Primitive {
+        if transform.is_identity() {
+            return self;
+        }
+        let map = |point| transform.apply(point);
+        match self {
+            Primitive::Move { end } => Primitive::Move { end: map(end) },
+            Primitive::Line { start, end } => Primitive::Line {
+                start: map(start),
+                end: map(end),
+            },
+            Primitive::Quadratic { start, p1, end } => Primitive::Quadratic {
+                start: map(start),
+                p1: map(p1),
+                end: map(end),
+            },
+            Primitive::Cubic { start, p1, p2, end } => Primitive::Cubic {
+                start: map(start),
+                p1: map(p1),
+                p2: map(p2),
+                end: map(end),
+            },
+            Primitive::Arc {
+                start,
+                end,
+                center,
+                rx,
+                ry,
+                x_rotation,
+                start_angle,
+                sweep_angle,
+            } => {
+                let (rx, ry, x_rotation, start_angle, sweep_angle) =
+                    transform.ellipse_arc(rx, ry, x_rotation, start_angle, sweep_angle);
+                Primitive::Arc {
+                    start: map(start),
+                    end: map(end),
+                    center: map(center),
+                    rx,
+                    ry,
+                    x_rotation,
+                    start_angle,
+                    sweep_angle,
+                }
+            }
+        }
+    }
+}
+
+/// Keeps `primitive` unless it is a `Move` to where the pen already is, which would only
+/// repeat its position. `pen` is the end of the previous primitive, `None` before the first.
+pub(crate) fn moves_the_pen(primitive: &Primitive, pen: &mut Option<Point>) -> bool {
+    let idle = matches!(*primitive, Primitive::Move { end } if Some(end) == *pen);
+    *pen = Some(primitive.end());
+    !idle
+}
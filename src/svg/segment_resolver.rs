@@ -0,0 +1,331 @@
+//! Resolving svgtypes path segments into absolute `Primitive`s, the only part of
+//! flattening that needs a path parser.
+
+use svgtypes::{PathCommand, PathSegment};
+
+use super::math::*;
+use super::point::*;
+use super::primitive::Primitive;
+
+/// Resolves path segments into absolute primitives without sampling them.
+/// Arcs whose end points coincide are dropped, as SVG requires.
+pub fn primitives_from_path_segments(
+    path_segments: impl Iterator<Item = PathSegment>,
+) -> impl Iterator<Item = Primitive> {
+    let mut resolver = SegmentResolver::default();
+    path_segments.filter_map(move |path_segment| resolver.resolve(path_segment))
+}
+
+/// Keeps track of the pen position, the sub-path start and the previous control point
+/// while path segments are resolved one by one.
+pub(crate) struct SegmentResolver {
+    current_point: Point,
+    prev_support_point_opt: Option<SupportPoint>,
+    path_start_point: Point,
+    path_start_point_initialized: bool,
+}
+
+impl Default for SegmentResolver {
+    fn default() -> Self {
+        SegmentResolver {
+            current_point: Point::ZERO,
+            prev_support_point_opt: None,
+            path_start_point: Point::ZERO,
+            path_start_point_initialized: false,
+        }
+    }
+}
+
+impl SegmentResolver {
+    pub(crate) fn resolve(&mut self, path_segment: PathSegment) -> Option<Primitive> {
+        let (primitive, support_point) = calc_primitive(
+            self.current_point,
+            path_segment,
+            self.prev_support_point_opt,
+            self.path_start_point,
+        );
+        self.prev_support_point_opt = support_point;
+        if let Some(primitive) = primitive {
+            self.current_point = primitive.end();
+        }
+
+        if !self.path_start_point_initialized && path_segment.cmd() != PathCommand::ClosePath {
+            self.path_start_point_initialized = true;
+            self.path_start_point = self.current_point;
+        } else if path_segment.cmd() == PathCommand::ClosePath {
+            self.path_start_point_initialized = false;
+        }
+
+        primitive
+    }
+}
+
+// === private members ===
+
+#[derive(Debug, Copy, Clone)]
+struct SupportPoint {
+    path_command: PathCommand,
+    point: Point,
+}
+
+//support point is always in absolute
+fn calc_primitive(
+    current: Point,
+    next_segment: PathSegment,
+    prev_support_point_opt: Option<SupportPoint>,
+    path_start_point: Point, //need that to implement ClosePath
+) -> (Option<Primitive>, Option<SupportPoint>) {
+    match next_segment {
+        PathSegment::MoveTo { abs, x, y } => (Some(move_to(current, abs, x, y)), None),
+        PathSegment::LineTo { abs, x, y } => (Some(line_to(current, abs, x, y)), None),
+        PathSegment::HorizontalLineTo { abs, x } => {
+            let miss_coord = if abs { current.y } else { 0. };
+            (Some(line_to(current, abs, x, miss_coord)), None)
+        }
+        PathSegment::VerticalLineTo { abs, y } => {
+            let miss_coord = if abs { current.x } else { 0. };
+            (Some(line_to(current, abs, miss_coord, y)), None)
+        }
+        PathSegment::CurveTo {
+            abs,
+            x1,
+            y1,
+            x2,
+            y2,
+            x,
+            y,
+        } => cubic_curve_to(current, abs, x1, y1, x2, y2, x, y, next_segment),
+        PathSegment::SmoothCurveTo { abs, x2, y2, x, y } => smooth_cubic_curve_to(
+            current,
+            abs,
+            x2,
+            y2,
+            x,
+            y,
+            prev_support_point_opt,
+            next_segment,
+        ),
+        PathSegment::Quadratic { abs, x1, y1, x, y } => {
+            quadratic_curve_to(current, abs, x1, y1, x, y, next_segment)
+        }
+        PathSegment::SmoothQuadratic { abs, x, y } => {
+            smooth_quadratic_curve_to(current, abs, x, y, prev_support_point_opt, next_segment)
+        }
+        PathSegment::EllipticalArc {
+            abs,
+            rx,
+            ry,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            x,
+            y,
+        } => (
+            ellipse_curve_to(
+                current,
+                abs,
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+            ),
+            None,
+        ),
+        PathSegment::ClosePath { abs: _ } => (
+            Some(line_to(
+                current,
+                true,
+                path_start_point.x,
+                path_start_point.y,
+            )),
+            None,
+        ),
+    }
+}
+
+fn move_to(current: Point, abs: bool, x: f64, y: f64) -> Primitive {
+    let end = absolute_point_coord(current, abs, x, y);
+    Primitive::Move { end }
+}
+
+fn line_to(current: Point, abs: bool, x: f64, y: f64) -> Primitive {
+    let end = absolute_point_coord(current, abs, x, y);
+    Primitive::Line {
+        start: current,
+        end,
+    }
+}
+
+fn cubic_curve_to(
+    current: Point,
+    abs: bool,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    x: f64,
+    y: f64,
+    next_segment: PathSegment,
+) -> (Option<Primitive>, Option<SupportPoint>) {
+    let p1 = absolute_point_coord(current, abs, x1, y1);
+    let p2 = absolute_point_coord(current, abs, x2, y2);
+    let end_point = absolute_point_coord(current, abs, x, y);
+    let support_point = Some(SupportPoint {
+        path_command: next_segment.cmd(),
+        point: p2,
+    });
+
+    let primitive = Primitive::Cubic {
+        start: current,
+        p1,
+        p2,
+        end: end_point,
+    };
+    (Some(primitive), support_point)
+}
+
+fn smooth_cubic_curve_to(
+    current: Point,
+    abs: bool,
+    x2: f64,
+    y2: f64,
+    x: f64,
+    y: f64,
+    prev_support_point_opt: Option<SupportPoint>,
+    next_segment: PathSegment,
+) -> (Option<Primitive>, Option<SupportPoint>) {
+    let p1 = mirrored_point(current, abs, prev_support_point_opt, CurveType::Cubic);
+    cubic_curve_to(current, abs, p1.x, p1.y, x2, y2, x, y, next_segment)
+}
+
+fn quadratic_curve_to(
+    current: Point,
+    abs: bool,
+    x1: f64,
+    y1: f64,
+    x: f64,
+    y: f64,
+    next_segment: PathSegment,
+) -> (Option<Primitive>, Option<SupportPoint>) {
+    let p1 = absolute_point_coord(current, abs, x1, y1);
+    let end_point = absolute_point_coord(current, abs, x, y);
+    let support_point = Some(SupportPoint {
+        path_command: next_segment.cmd(),
+        point: Point { x: p1.x, y: p1.y },
+    });
+
+    let primitive = Primitive::Quadratic {
+        start: current,
+        p1,
+        end: end_point,
+    };
+    (Some(primitive), support_point)
+}
+
+fn smooth_quadratic_curve_to(
+    current: Point,
+    abs: bool,
+    x: f64,
+    y: f64,
+    prev_support_point_opt: Option<SupportPoint>,
+    next_segment: PathSegment,
+) -> (Option<Primitive>, Option<SupportPoint>) {
+    let p1 = mirrored_point(current, abs, prev_support_point_opt, CurveType::Quadratic);
+    quadratic_curve_to(current, abs, p1.x, p1.y, x, y, next_segment)
+}
+
+fn ellipse_curve_to(
+    current: Point,
+    abs: bool,
+    rx: f64,
+    ry: f64,
+    x_axis_rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    end_x: f64,
+    end_y: f64,
+) -> Option<Primitive> {
+    let end_point = absolute_point_coord(current, abs, end_x, end_y);
+
+    // If the endpoints are identical, then this is equivalent to omitting the elliptical arc segment entirely.
+    if current == end_point {
+        return None;
+    }
+
+    // If rx = 0 or ry = 0 then this arc is treated as a straight line segment joining the endpoints.
+    if rx == 0. || ry == 0. {
+        return Some(line_to(current, abs, end_x, end_y));
+    }
+
+    let (start_angle, sweep_angle, rx_abs, ry_abs, x_rad_rotation, center_x, center_y) =
+        ellipse_support_calc(
+            current,
+            rx,
+            ry,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            end_point.x,
+            end_point.y,
+        );
+
+    Some(Primitive::Arc {
+        start: current,
+        end: end_point,
+        center: Point::new(center_x, center_y),
+        rx: rx_abs,
+        ry: ry_abs,
+        x_rotation: x_rad_rotation,
+        start_angle,
+        sweep_angle,
+    })
+}
+
+fn absolute_point_coord(start: Point, abs: bool, x: f64, y: f64) -> Point {
+    match abs {
+        true => Point { x, y },
+        false => Point { x, y } + start,
+    }
+}
+
+enum CurveType {
+    Cubic,
+    Quadratic,
+}
+
+fn path_command_condition(prev_support_point: &SupportPoint, curve_type: CurveType) -> bool {
+    match curve_type {
+        CurveType::Cubic => {
+            prev_support_point.path_command == PathCommand::SmoothCurveTo
+                || prev_support_point.path_command == PathCommand::CurveTo
+        }
+
+        CurveType::Quadratic => {
+            prev_support_point.path_command == PathCommand::SmoothQuadratic
+                || prev_support_point.path_command == PathCommand::Quadratic
+        }
+    }
+}
+
+fn mirrored_point(
+    current: Point,
+    abs: bool,
+    prev_support_point_opt: Option<SupportPoint>,
+    curve_type: CurveType,
+) -> Point {
+    let mut mirrored_point = match prev_support_point_opt {
+        Some(ref prev_support_point) if path_command_condition(prev_support_point, curve_type) => {
+            current - prev_support_point.point
+        }
+        _ => Point::ZERO,
+    };
+
+    if abs {
+        mirrored_point = mirrored_point + current;
+    }
+
+    mirrored_point
+}
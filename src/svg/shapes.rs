@@ -0,0 +1,179 @@
+//! The SVG basic shapes as path segments, following the equivalent paths given in the
+//! SVG 2 specification. Shapes that would not render (zero or negative sizes) give no
+//! segments at all.
+
+use core::iter;
+use svgtypes::{PathSegment, PointsParser};
+
+/// `<rect>`. A missing `rx` or `ry` takes the value of the other one, and both are
+/// limited to half the width and height.
+pub fn rect_segments(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    rx: Option<f64>,
+    ry: Option<f64>,
+) -> impl Iterator<Item = PathSegment> {
+    let mut segments = ShapeSegments::default();
+    if !(width > 0. && height > 0.) {
+        return segments;
+    }
+
+    let valid_radius = |radius: Option<f64>| radius.filter(|radius| *radius >= 0.);
+    let (rx, ry) = match (valid_radius(rx), valid_radius(ry)) {
+        (Some(rx), Some(ry)) => (rx, ry),
+        (Some(radius), None) | (None, Some(radius)) => (radius, radius),
+        (None, None) => (0., 0.),
+    };
+    let rx = rx.min(width / 2.);
+    let ry = ry.min(height / 2.);
+    let right = x + width;
+    let bottom = y + height;
+
+    if rx == 0. || ry == 0. {
+        segments.push(move_to(x, y));
+        segments.push(PathSegment::HorizontalLineTo {
+            abs: true,
+            x: right,
+        });
+        segments.push(PathSegment::VerticalLineTo {
+            abs: true,
+            y: bottom,
+        });
+        segments.push(PathSegment::HorizontalLineTo { abs: true, x });
+    } else {
+        segments.push(move_to(x + rx, y));
+        segments.push(PathSegment::HorizontalLineTo {
+            abs: true,
+            x: right - rx,
+        });
+        segments.push(arc_to(rx, ry, right, y + ry));
+        segments.push(PathSegment::VerticalLineTo {
+            abs: true,
+            y: bottom - ry,
+        });
+        segments.push(arc_to(rx, ry, right - rx, bottom));
+        segments.push(PathSegment::HorizontalLineTo {
+            abs: true,
+            x: x + rx,
+        });
+        segments.push(arc_to(rx, ry, x, bottom - ry));
+        segments.push(PathSegment::VerticalLineTo {
+            abs: true,
+            y: y + ry,
+        });
+        segments.push(arc_to(rx, ry, x + rx, y));
+    }
+    segments.push(PathSegment::ClosePath { abs: true });
+    segments
+}
+
+/// `<circle>`, drawn clockwise from its rightmost point.
+pub fn circle_segments(cx: f64, cy: f64, r: f64) -> impl Iterator<Item = PathSegment> {
+    ellipse_segments(cx, cy, r, r)
+}
+
+/// `<ellipse>`, drawn clockwise from its rightmost point.
+pub fn ellipse_segments(cx: f64, cy: f64, rx: f64, ry: f64) -> impl Iterator<Item = PathSegment> {
+    let mut segments = ShapeSegments::default();
+    if !(rx > 0. && ry > 0.) {
+        return segments;
+    }
+
+    segments.push(move_to(cx + rx, cy));
+    segments.push(arc_to(rx, ry, cx, cy + ry));
+    segments.push(arc_to(rx, ry, cx - rx, cy));
+    segments.push(arc_to(rx, ry, cx, cy - ry));
+    segments.push(arc_to(rx, ry, cx + rx, cy));
+    segments.push(PathSegment::ClosePath { abs: true });
+    segments
+}
+
+/// `<line>`.
+pub fn line_segments(x1: f64, y1: f64, x2: f64, y2: f64) -> impl Iterator<Item = PathSegment> {
+    let mut segments = ShapeSegments::default();
+    segments.push(move_to(x1, y1));
+    segments.push(PathSegment::LineTo {
+        abs: true,
+        x: x2,
+        y: y2,
+    });
+    segments
+}
+
+/// `<polyline>` from its `points` attribute; points after a parse error are dropped.
+pub fn polyline_segments(points: &str) -> impl Iterator<Item = PathSegment> + '_ {
+    PointsParser::from(points)
+        .enumerate()
+        .map(|(index, (x, y))| match index {
+            0 => move_to(x, y),
+            _ => PathSegment::LineTo { abs: true, x, y },
+        })
+}
+
+/// `<polygon>` from its `points` attribute: a polyline that is closed at the end.
+pub fn polygon_segments(points: &str) -> impl Iterator<Item = PathSegment> + '_ {
+    let has_points = PointsParser::from(points).next().is_some();
+    polyline_segments(points)
+        .chain(iter::once(PathSegment::ClosePath { abs: true }).filter(move |_| has_points))
+}
+
+// === private members ===
+
+const MAX_SHAPE_SEGMENTS: usize = 10;
+
+/// Up to ten segments in place, so that shapes need no allocator.
+struct ShapeSegments {
+    segments: [PathSegment; MAX_SHAPE_SEGMENTS],
+    len: usize,
+    next: usize,
+}
+
+impl Default for ShapeSegments {
+    fn default() -> Self {
+        ShapeSegments {
+            segments: [PathSegment::ClosePath { abs: true }; MAX_SHAPE_SEGMENTS],
+            len: 0,
+            next: 0,
+        }
+    }
+}
+
+impl ShapeSegments {
+    fn push(&mut self, segment: PathSegment) {
+        self.segments[self.len] = segment;
+        self.len += 1;
+    }
+}
+
+impl Iterator for ShapeSegments {
+    type Item = PathSegment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.len {
+            None
+        } else {
+            self.next += 1;
+            Some(self.segments[self.next - 1])
+        }
+    }
+}
+
+fn move_to(x: f64, y: f64) -> PathSegment {
+    PathSegment::MoveTo { abs: true, x, y }
+}
+
+// quarter of an axis aligned ellipse, turning clockwise on screen
+fn arc_to(rx: f64, ry: f64, x: f64, y: f64) -> PathSegment {
+    PathSegment::EllipticalArc {
+        abs: true,
+        rx,
+        ry,
+        x_axis_rotation: 0.,
+        large_arc: false,
+        sweep: true,
+        x,
+        y,
+    }
+}
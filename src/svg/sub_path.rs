@@ -0,0 +1,135 @@
+//! Flattened `LineTo` streams cut into the runs the pen draws without lifting, and put
+//! back together.
+
+use alloc::vec::Vec;
+
+use super::line_to::{ArcChords, LineTo};
+use super::point::Point;
+use crate::float::floor;
+
+/// The points of one pen-down run, starting with the point the pen was lowered at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubPath {
+    pub points: Vec<Point>,
+}
+
+impl SubPath {
+    /// Ends where it starts, as `ClosePath` and the closed basic shapes do.
+    pub fn is_closed(&self) -> bool {
+        self.points.len() > 2 && self.points.first() == self.points.last()
+    }
+
+    /// Walks the sub-path the other way round.
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+    }
+
+    /// Lets a closed sub-path start and end at `points[index]` instead, drawing the same
+    /// loop. Open sub-paths are left alone.
+    pub fn rotate_start(&mut self, index: usize) {
+        if self.is_closed() {
+            self.points.pop();
+            let len = self.points.len();
+            self.points.rotate_left(index % len);
+            self.points.push(self.points[0]);
+        }
+    }
+
+    /// The sub-path as moves again: a `Fly` to its start, then `Draw`s.
+    pub fn line_tos(&self) -> impl Iterator<Item = LineTo> + '_ {
+        self.points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| match index {
+                0 => LineTo::Fly(*point),
+                _ => LineTo::Draw(*point),
+            })
+    }
+}
+
+/// Cuts a flattened stream at every `Fly`. Arcs are replaced by chords at most 2
+/// degrees apart, `Erase` moves count as drawn. Runs that never leave their first
+/// point are dropped.
+pub fn sub_paths(line_tos: impl IntoIterator<Item = LineTo>) -> Vec<SubPath> {
+    let mut sub_paths = Vec::new();
+    let mut points = Vec::new();
+    let mut pen = Point::ZERO;
+    for line_to in line_tos {
+        match line_to {
+            LineTo::Fly(point) => {
+                finish_sub_path(&mut sub_paths, &mut points);
+                points.push(point);
+            }
+            LineTo::Draw(point) | LineTo::Erase(point) => {
+                if points.is_empty() {
+                    points.push(pen);
+                }
+                points.push(point);
+            }
+            LineTo::Arc(arc) => {
+                if points.is_empty() {
+                    points.push(pen);
+                }
+                points.extend(ArcChords::new(pen, &arc));
+            }
+        }
+        pen = line_to.target();
+    }
+    finish_sub_path(&mut sub_paths, &mut points);
+    sub_paths
+}
+
+/// Puts `sub_paths` back into a stream: a `Fly` to the start of each, then `Draw`s. The
+/// passes over sub-paths take a stream through `sub_paths` and this, so their results
+/// start with a `Fly` and only have `Fly` and `Draw` moves.
+pub fn line_tos_from_sub_paths(sub_paths: &[SubPath]) -> Vec<LineTo> {
+    sub_paths.iter().flat_map(SubPath::line_tos).collect()
+}
+
+/// Square cells for bucketing points, so that those near a point are found without
+/// looking at all of them.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct PointGrid {
+    pub(crate) cell: f64,
+}
+
+impl PointGrid {
+    /// Cells are `cell` wide, or a tiny bit wider if that is zero.
+    pub(crate) fn new(cell: f64) -> Self {
+        PointGrid {
+            cell: cell.max(MIN_CELL),
+        }
+    }
+
+    pub(crate) fn key(&self, point: Point) -> (i64, i64) {
+        (
+            floor(point.x / self.cell) as i64,
+            floor(point.y / self.cell) as i64,
+        )
+    }
+
+    /// The cell of `point` and the eight around it, which hold every point within one
+    /// cell of it.
+    pub(crate) fn around(&self, point: Point) -> impl Iterator<Item = (i64, i64)> {
+        let (column, row) = self.key(point);
+        (row.saturating_sub(1)..=row.saturating_add(1)).flat_map(move |row| {
+            (column.saturating_sub(1)..=column.saturating_add(1)).map(move |column| (column, row))
+        })
+    }
+}
+
+// === private members ===
+
+// cells are never smaller than this, so that a zero tolerance still gives sensible cells
+const MIN_CELL: f64 = 1e-9;
+
+fn finish_sub_path(sub_paths: &mut Vec<SubPath>, points: &mut Vec<Point>) {
+    let first = points.first().copied();
+    if points.iter().any(|point| Some(*point) != first) {
+        sub_paths.push(SubPath {
+            points: core::mem::take(points),
+        });
+    } else {
+        points.clear();
+    }
+}
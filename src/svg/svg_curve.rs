@@ -1,17 +1,27 @@
-use svgtypes::{PathCommand, PathSegment};
+use crate::float::ceil;
+#[cfg(feature = "svgtypes")]
+use svgtypes::PathSegment;
 
+use super::arc_length_timer::ArcLengthTimer;
+use super::flatten_options::*;
+use super::forward_differences::ForwardDifferences;
 use super::math::*;
+#[cfg(feature = "svgtypes")]
+use super::path_error::*;
 use super::point::*;
+use super::primitive::*;
+#[cfg(feature = "svgtypes")]
+use super::segment_resolver::SegmentResolver;
 use super::tick_timer::TickTimer;
+#[cfg(feature = "svgtypes")]
+use super::transform::Transform;
+use crate::scalar::Scalar;
 
-pub enum LineTo {
-    Fly(Point),
-    Draw(Point),
-    Erase(Point),
-}
+// the moves themselves live in `line_to`, which builds without a path parser
+pub use super::line_to::{CircularArc, LineTo};
 
-impl LineTo {
-    fn new(point: Point, move_type: MoveType) -> Self {
+impl<S: Scalar> LineTo<S> {
+    fn new(point: Point<S>, move_type: MoveType) -> Self {
         match move_type {
             MoveType::Fly => LineTo::Fly(point),
             MoveType::Draw => LineTo::Draw(point),
@@ -20,448 +30,448 @@ impl LineTo {
     }
 }
 
+/// Flattens path segments into pen moves. A move to where the pen already is gives no
+/// `Fly`.
+#[cfg(feature = "svgtypes")]
 pub fn points_from_path_segments(
     path_segments: impl Iterator<Item = PathSegment>,
 ) -> impl Iterator<Item = LineTo> {
-    let mut current_point = Point::ZERO;
-    let mut prev_support_point_opt: Option<SupportPoint> = None;
-    let mut path_start_point = Point::ZERO;
-    let mut path_start_point_initialized = false;
-
-    path_segments.flat_map(move |path_segment| {
-        let point_iterator = calc_point_iterator(
-            current_point,
-            path_segment,
-            prev_support_point_opt,
-            path_start_point,
-        );
-        prev_support_point_opt = point_iterator.support_point();
-        current_point = point_iterator.end_position();
-
-        if !path_start_point_initialized && path_segment.cmd() != PathCommand::ClosePath {
-            path_start_point_initialized = true;
-            path_start_point = current_point;
-        } else if path_segment.cmd() == PathCommand::ClosePath {
-            path_start_point_initialized = false;
-        }
+    points_from_path_segments_as(path_segments)
+}
 
-        let move_type = point_iterator.move_type();
-        point_iterator.map(move |point| LineTo::new(point, move_type))
-    })
+/// Like `points_from_path_segments`, but every curve is split into as few chords as
+/// needed to keep them within `tolerance` (in output units) of the real curve.
+#[cfg(feature = "svgtypes")]
+pub fn points_from_path_segments_with_tolerance(
+    path_segments: impl Iterator<Item = PathSegment>,
+    tolerance: f64,
+) -> impl Iterator<Item = LineTo> {
+    points_from_path_segments_with_tolerance_as(path_segments, tolerance)
 }
 
-// === private members ===
+/// Like `points_from_path_segments`, with every flattening knob set by the caller.
+#[cfg(feature = "svgtypes")]
+pub fn points_from_path_segments_with_options(
+    path_segments: impl Iterator<Item = PathSegment>,
+    options: FlattenOptions,
+) -> impl Iterator<Item = LineTo> {
+    points_from_path_segments_with_options_as(path_segments, options)
+}
 
-#[derive(PartialEq, Copy, Clone)]
-enum MoveType {
-    Fly,
-    Draw,
-    Erase,
+/// Like `points_from_path_segments`, but for unchecked input such as a `PathParser`:
+/// parse errors and NaN or infinite numbers end the stream with a `PathError` naming
+/// the segment, instead of sending the pen somewhere else.
+#[cfg(feature = "svgtypes")]
+pub fn try_points_from_path_segments(
+    path_segments: impl Iterator<Item = Result<PathSegment, svgtypes::Error>>,
+) -> impl Iterator<Item = Result<LineTo, PathError>> {
+    try_points_from_path_segments_as(path_segments)
 }
 
-#[derive(Debug, Copy, Clone)]
-struct SupportPoint {
-    path_command: PathCommand,
-    point: Point,
+/// Like `try_points_from_path_segments`, with every flattening knob set by the caller.
+/// Everything before the failing segment is still emitted.
+#[cfg(feature = "svgtypes")]
+pub fn try_points_from_path_segments_with_options(
+    path_segments: impl Iterator<Item = Result<PathSegment, svgtypes::Error>>,
+    options: FlattenOptions,
+) -> impl Iterator<Item = Result<LineTo, PathError>> {
+    try_points_from_path_segments_with_options_as(path_segments, options)
 }
 
-// === === === EMPTY === === ===
-struct EmptyPointIterator {
-    end: Point,
+/// `points_from_path_segments` with points computed in `S`, which is `f64`, `f32` or
+/// `scalar::Fixed` (see the `scalar` module), e.g. `points_from_path_segments_as::<f32>`.
+#[cfg(feature = "svgtypes")]
+pub fn points_from_path_segments_as<S: Scalar>(
+    path_segments: impl Iterator<Item = PathSegment>,
+) -> impl Iterator<Item = LineTo<S>> {
+    points_from_path_segments_with_options_as(path_segments, Default::default())
 }
 
-// === === === LINE === === ===
-struct LinePointIterator {
-    end: Point,
-    move_type: MoveType,
-    done: bool,
-    support_point: Option<SupportPoint>,
+/// `points_from_path_segments_with_tolerance` with points computed in `S`.
+#[cfg(feature = "svgtypes")]
+pub fn points_from_path_segments_with_tolerance_as<S: Scalar>(
+    path_segments: impl Iterator<Item = PathSegment>,
+    tolerance: f64,
+) -> impl Iterator<Item = LineTo<S>> {
+    points_from_path_segments_with_options_as(
+        path_segments,
+        FlattenOptions::with_tolerance(tolerance),
+    )
 }
 
-impl LinePointIterator {
-    fn new(end: Point, move_type: MoveType) -> Self {
-        LinePointIterator {
-            end,
-            move_type,
-            done: false,
-            support_point: None,
-        }
-    }
+/// `points_from_path_segments_with_options` with points computed in `S`.
+#[cfg(feature = "svgtypes")]
+pub fn points_from_path_segments_with_options_as<S: Scalar>(
+    path_segments: impl Iterator<Item = PathSegment>,
+    options: FlattenOptions,
+) -> impl Iterator<Item = LineTo<S>> {
+    points_from_primitives_as(primitives_from_path_segments(path_segments), options)
+}
 
-    fn with_support(end: Point, move_type: MoveType, support_point: Option<SupportPoint>) -> Self {
-        LinePointIterator {
-            end,
-            move_type,
-            done: false,
-            support_point,
-        }
-    }
+/// `try_points_from_path_segments` with points computed in `S`.
+#[cfg(feature = "svgtypes")]
+pub fn try_points_from_path_segments_as<S: Scalar>(
+    path_segments: impl Iterator<Item = Result<PathSegment, svgtypes::Error>>,
+) -> impl Iterator<Item = Result<LineTo<S>, PathError>> {
+    try_points_from_path_segments_with_options_as(path_segments, Default::default())
 }
 
-// === === === CURVE === === ===
-struct SquareCurvePointIterator {
-    time: TickTimer,
-    calc_formula: SquareCurve,
-    support_point: Option<SupportPoint>,
+/// `try_points_from_path_segments_with_options` with points computed in `S`.
+#[cfg(feature = "svgtypes")]
+pub fn try_points_from_path_segments_with_options_as<S: Scalar>(
+    path_segments: impl Iterator<Item = Result<PathSegment, svgtypes::Error>>,
+    options: FlattenOptions,
+) -> impl Iterator<Item = Result<LineTo<S>, PathError>> {
+    let mut resolver = SegmentResolver::default();
+    let mut pen = None;
+    path_segments
+        .enumerate()
+        .map(move |(index, path_segment)| {
+            checked_primitive(&mut resolver, path_segment, &options.transform).map_err(|kind| {
+                PathError {
+                    segment: index,
+                    kind,
+                }
+            })
+        })
+        .scan(false, |failed, result| {
+            if *failed {
+                return None;
+            }
+            *failed = result.is_err();
+            Some(result)
+        })
+        .flat_map(move |result| {
+            let (primitive, error) = match result {
+                Ok(primitive) => (
+                    primitive.filter(|primitive| moves_the_pen(primitive, &mut pen)),
+                    None,
+                ),
+                Err(error) => (None, Some(error)),
+            };
+            primitive
+                .into_iter()
+                .flat_map(move |primitive| primitive_line_tos(primitive, options))
+                .map(Ok)
+                .chain(error.map(Err))
+        })
 }
 
-struct CubicCurvePointIterator {
-    time: TickTimer,
-    calc_formula: CubicCurve,
-    support_point: Option<SupportPoint>,
+/// Flattens primitives resolved elsewhere into pen moves, with the same options as
+/// `points_from_path_segments_with_options`. Needs no path parser, so it is what bare
+/// metal builds without the `svgtypes` feature flatten with.
+pub fn points_from_primitives(
+    primitives: impl Iterator<Item = Primitive>,
+    options: FlattenOptions,
+) -> impl Iterator<Item = LineTo> {
+    points_from_primitives_as(primitives, options)
 }
 
-// === === === ELLIPSE === === ===
-struct EllipsePointIterator {
-    time: TickTimer,
-    calc_formula: EllipseCurve,
-    end: Point,
+/// `points_from_primitives` with points computed in `S`.
+pub fn points_from_primitives_as<S: Scalar>(
+    primitives: impl Iterator<Item = Primitive>,
+    options: FlattenOptions,
+) -> impl Iterator<Item = LineTo<S>> {
+    let mut pen = None;
+    primitives
+        .map(move |primitive| primitive.transformed(&options.transform))
+        .filter(move |primitive| moves_the_pen(primitive, &mut pen))
+        .flat_map(move |primitive| primitive_line_tos(primitive, options))
 }
 
-// === === === POINT ITERATOR === === ===
-enum PointIterator {
-    Empty(EmptyPointIterator),
-    Line(LinePointIterator),
-    SquareCurve(SquareCurvePointIterator),
-    CubicCurve(CubicCurvePointIterator),
-    EllipseCurve(EllipsePointIterator),
+// === private members ===
+
+fn primitive_line_tos<S: Scalar>(
+    primitive: Primitive,
+    options: FlattenOptions,
+) -> impl Iterator<Item = LineTo<S>> {
+    let point_iterator = calc_point_iterator(primitive, options);
+    let move_type = point_iterator.move_type();
+    point_iterator.map(move |point| LineTo::new(point, move_type))
 }
 
-//todo: looks like I can remove one layer of abstraction!
-impl PointIterator {
-    //support point is always in absolute
-    fn support_point(&self) -> Option<SupportPoint> {
-        match self {
-            PointIterator::Empty(_) => None,
-            PointIterator::Line(iter) => iter.support_point,
-            PointIterator::SquareCurve(iter) => iter.support_point,
-            PointIterator::CubicCurve(iter) => iter.support_point,
-            PointIterator::EllipseCurve(_) => None,
+// `None` for segments that draw nothing, like arcs back to their start
+#[cfg(feature = "svgtypes")]
+fn checked_primitive(
+    resolver: &mut SegmentResolver,
+    path_segment: Result<PathSegment, svgtypes::Error>,
+    transform: &Transform,
+) -> Result<Option<Primitive>, PathErrorKind> {
+    let path_segment = path_segment?;
+    check_segment(&path_segment)?;
+    match resolver.resolve(path_segment) {
+        Some(primitive) => {
+            let primitive = primitive.transformed(transform);
+            check_primitive(&primitive)?;
+            Ok(Some(primitive))
         }
+        None => Ok(None),
     }
+}
 
-    fn end_position(&self) -> Point {
+impl Flattening {
+    fn curve_timer<S: Scalar>(self, curve: &impl CurvePoint<S>) -> CurveTimer<S> {
         match self {
-            PointIterator::Empty(iter) => iter.end,
-            PointIterator::Line(iter) => iter.end,
-            PointIterator::SquareCurve(iter) => iter.calc_formula.at(1.0),
-            PointIterator::CubicCurve(iter) => iter.calc_formula.at(1.0),
-            PointIterator::EllipseCurve(iter) => iter.end,
+            Flattening::FixedStep(period) => {
+                CurveTimer::uniform(curve, TickTimer::with_period(period).ticks())
+            }
+            Flattening::Tolerance(tolerance) => {
+                CurveTimer::uniform(curve, curve.ticks_for_tolerance(tolerance))
+            }
+            Flattening::ArcLength(spacing) => {
+                CurveTimer::ArcLength(ArcLengthTimer::new(curve, spacing))
+            }
         }
     }
+}
 
-    fn move_type(&self) -> MoveType {
-        match self {
-            PointIterator::Empty(_) => MoveType::Fly,
-            PointIterator::Line(iter) => iter.move_type,
-            PointIterator::SquareCurve(_) => MoveType::Draw,
-            PointIterator::CubicCurve(_) => MoveType::Draw,
-            PointIterator::EllipseCurve(_) => MoveType::Draw,
+#[derive(PartialEq, Copy, Clone)]
+enum MoveType {
+    Fly,
+    Draw,
+    Erase,
+}
+
+// === === === LINE === === ===
+struct LinePointIterator<S: Scalar> {
+    start: Point<S>,
+    end: Point<S>,
+    move_type: MoveType,
+    steps: usize,
+    step: usize,
+}
+
+impl<S: Scalar> LinePointIterator<S> {
+    fn new(start: Point, end: Point, move_type: MoveType, options: FlattenOptions) -> Self {
+        let steps = match options.flattening {
+            _ if move_type != MoveType::Fly && start == end => 0,
+            Flattening::ArcLength(spacing) if move_type != MoveType::Fly => {
+                clamp_ticks(ceil(distance(start, end) / spacing))
+            }
+            _ => 1,
+        };
+        LinePointIterator {
+            start: start.cast(),
+            end: end.cast(),
+            move_type,
+            steps,
+            step: 0,
         }
     }
 }
 
-impl Iterator for PointIterator {
-    type Item = Point;
+impl<S: Scalar> Iterator for LinePointIterator<S> {
+    type Item = Point<S>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            PointIterator::Empty(_) => None,
-            PointIterator::Line(iter) => {
-                if iter.done {
-                    None
-                } else {
-                    iter.done = true;
-                    Some(iter.end)
-                }
-            }
-            PointIterator::SquareCurve(iter) => {
-                iter.time.next().map(|time| iter.calc_formula.at(time))
-            }
-            PointIterator::CubicCurve(iter) => {
-                iter.time.next().map(|time| iter.calc_formula.at(time))
-            }
-            PointIterator::EllipseCurve(iter) => {
-                iter.time.next().map(|time| iter.calc_formula.at(time))
+        if self.step >= self.steps {
+            None
+        } else {
+            self.step += 1;
+            if self.step == self.steps {
+                Some(self.end)
+            } else {
+                let ratio = S::from_ratio(self.step, self.steps);
+                Some(self.start + (self.end - self.start) * ratio)
             }
         }
     }
 }
 
-fn calc_point_iterator(
-    current: Point,
-    next_segment: PathSegment,
-    prev_support_point_opt: Option<SupportPoint>,
-    path_start_point: Point, //need that to implement ClosePath
-) -> PointIterator {
-    match next_segment {
-        PathSegment::MoveTo { abs, x, y } => move_to(current, abs, x, y),
-        PathSegment::LineTo { abs, x, y } => line_to(current, abs, x, y),
-        PathSegment::HorizontalLineTo { abs, x } => {
-            let miss_coord = if abs { current.y } else { 0. };
-            line_to(current, abs, x, miss_coord)
-        }
-        PathSegment::VerticalLineTo { abs, y } => {
-            let miss_coord = if abs { current.x } else { 0. };
-            line_to(current, abs, miss_coord, y)
-        }
-        PathSegment::CurveTo {
-            abs,
-            x1,
-            y1,
-            x2,
-            y2,
-            x,
-            y,
-        } => cubic_curve_to(current, abs, x1, y1, x2, y2, x, y, next_segment),
-        PathSegment::SmoothCurveTo { abs, x2, y2, x, y } => smooth_cubic_curve_to(
-            current,
-            abs,
-            x2,
-            y2,
-            x,
-            y,
-            prev_support_point_opt,
-            next_segment,
-        ),
-        PathSegment::Quadratic { abs, x1, y1, x, y } => {
-            quadratic_curve_to(current, abs, x1, y1, x, y, next_segment)
-        }
-        PathSegment::SmoothQuadratic { abs, x, y } => {
-            smooth_quadratic_curve_to(current, abs, x, y, prev_support_point_opt, next_segment)
+// === === === CURVE === === ===
+enum CurveTimer<S: Scalar> {
+    Ticks(TickTimer),
+    ForwardDifferences(ForwardDifferences<S>),
+    ArcLength(ArcLengthTimer<S>),
+}
+
+impl<S: Scalar> CurveTimer<S> {
+    // equal parameter steps, walked incrementally where the curve allows it
+    fn uniform(curve: &impl CurvePoint<S>, ticks: usize) -> Self {
+        match curve.forward_differences(ticks) {
+            Some(differences) => CurveTimer::ForwardDifferences(differences),
+            None => CurveTimer::Ticks(TickTimer::with_ticks(ticks)),
         }
-        PathSegment::EllipticalArc {
-            abs,
-            rx,
-            ry,
-            x_axis_rotation,
-            large_arc,
-            sweep,
-            x,
-            y,
-        } => ellipse_curve_to(
-            current,
-            abs,
-            rx,
-            ry,
-            x_axis_rotation,
-            large_arc,
-            sweep,
-            x,
-            y,
-        ),
-        PathSegment::ClosePath { abs: _ } => {
-            line_to(current, true, path_start_point.x, path_start_point.y)
+    }
+
+    fn next_point(&mut self, curve: &impl CurvePoint<S>) -> Option<Point<S>> {
+        match self {
+            CurveTimer::Ticks(timer) => timer.next_time().map(|time| curve.at(time)),
+            CurveTimer::ForwardDifferences(differences) => differences.next(),
+            CurveTimer::ArcLength(timer) => timer.next_time(curve).map(|time| curve.at(time)),
         }
     }
-}
 
-fn move_to(current: Point, abs: bool, x: f64, y: f64) -> PointIterator {
-    let end_point = absolute_point_coord(current, abs, x, y);
-    PointIterator::Line(LinePointIterator::new(end_point, MoveType::Fly))
+    fn is_finished(&self) -> bool {
+        match self {
+            CurveTimer::Ticks(timer) => timer.is_finished(),
+            CurveTimer::ForwardDifferences(differences) => differences.is_finished(),
+            CurveTimer::ArcLength(timer) => timer.is_finished(),
+        }
+    }
 }
 
-fn line_to(current: Point, abs: bool, x: f64, y: f64) -> PointIterator {
-    let end_point = absolute_point_coord(current, abs, x, y);
-    PointIterator::Line(LinePointIterator::new(end_point, MoveType::Draw))
+/// Turns curve times into points. The last point is always the exact `end`, and a point
+/// equal to the one before it is never emitted.
+struct CurveSampler<S: Scalar> {
+    time: CurveTimer<S>,
+    last_point: Point<S>,
+    end: Point<S>,
+    min_segment_length: S,
 }
 
-fn cubic_curve_to(
-    current: Point,
-    abs: bool,
-    x1: f64,
-    y1: f64,
-    x2: f64,
-    y2: f64,
-    x: f64,
-    y: f64,
-    next_segment: PathSegment,
-) -> PointIterator {
-    let time: TickTimer = Default::default();
-    let p1 = absolute_point_coord(current, abs, x1, y1);
-    let p2 = absolute_point_coord(current, abs, x2, y2);
-    let end_point = absolute_point_coord(current, abs, x, y);
-    let support_point = Some(SupportPoint {
-        path_command: next_segment.cmd(),
-        point: p2,
-    });
-
-    let p1_on_lane = is_point_on_lane(current, end_point, &p1);
-    let p2_on_lane = is_point_on_lane(current, end_point, &p2);
-
-    if p1_on_lane && p2_on_lane {
-        PointIterator::Line(LinePointIterator::with_support(
-            end_point,
-            MoveType::Draw,
-            support_point,
-        ))
-    } else {
-        let calc_formula = CubicCurve::new(current, p1, p2, end_point);
-        let cubic_curve_iterator = CubicCurvePointIterator {
+impl<S: Scalar> CurveSampler<S> {
+    fn new(time: CurveTimer<S>, start: Point, end: Point, options: FlattenOptions) -> Self {
+        CurveSampler {
             time,
-            calc_formula,
-            support_point,
-        };
-        PointIterator::CubicCurve(cubic_curve_iterator)
+            last_point: start.cast(),
+            end: end.cast(),
+            min_segment_length: S::from_f64(options.min_segment_length),
+        }
     }
-}
-
-fn smooth_cubic_curve_to(
-    current: Point,
-    abs: bool,
-    x2: f64,
-    y2: f64,
-    x: f64,
-    y: f64,
-    prev_support_point_opt: Option<SupportPoint>,
-    next_segment: PathSegment,
-) -> PointIterator {
-    let p1 = mirrored_point(current, abs, prev_support_point_opt, CurveType::Cubic);
-    cubic_curve_to(current, abs, p1.x, p1.y, x2, y2, x, y, next_segment)
-}
 
-fn quadratic_curve_to(
-    current: Point,
-    abs: bool,
-    x1: f64,
-    y1: f64,
-    x: f64,
-    y: f64,
-    next_segment: PathSegment,
-) -> PointIterator {
-    let time: TickTimer = Default::default();
-    let p1 = absolute_point_coord(current, abs, x1, y1);
-    let end_point = absolute_point_coord(current, abs, x, y);
-    let support_point = Some(SupportPoint {
-        path_command: next_segment.cmd(),
-        point: Point { x: p1.x, y: p1.y },
-    });
-
-    let p1_on_lane = is_point_on_lane(current, end_point, &p1);
-    if p1_on_lane {
-        PointIterator::Line(LinePointIterator::with_support(
-            end_point,
-            MoveType::Draw,
-            support_point,
-        ))
-    } else {
-        let calc_formula = SquareCurve::new(current, p1, end_point);
-        let square_curve_iterator = SquareCurvePointIterator {
-            time,
-            calc_formula,
-            support_point,
-        };
-        PointIterator::SquareCurve(square_curve_iterator)
+    fn next_point(&mut self, curve: &impl CurvePoint<S>) -> Option<Point<S>> {
+        while let Some(point) = self.time.next_point(curve) {
+            let point = if self.time.is_finished() {
+                self.end
+            } else {
+                point
+            };
+            if point == self.last_point {
+                continue;
+            }
+            if self.time.is_finished()
+                || distance(self.last_point, point) >= self.min_segment_length
+            {
+                self.last_point = point;
+                return Some(point);
+            }
+        }
+        None
     }
 }
 
-fn smooth_quadratic_curve_to(
-    current: Point,
-    abs: bool,
-    x: f64,
-    y: f64,
-    prev_support_point_opt: Option<SupportPoint>,
-    next_segment: PathSegment,
-) -> PointIterator {
-    let p1 = mirrored_point(current, abs, prev_support_point_opt, CurveType::Quadratic);
-    quadratic_curve_to(current, abs, p1.x, p1.y, x, y, next_segment)
+struct SquareCurvePointIterator<S: Scalar> {
+    sampler: CurveSampler<S>,
+    calc_formula: SquareCurve<S>,
 }
 
-fn ellipse_curve_to(
-    current: Point,
-    abs: bool,
-    rx: f64,
-    ry: f64,
-    x_axis_rotation: f64,
-    large_arc: bool,
-    sweep: bool,
-    end_x: f64,
-    end_y: f64,
-) -> PointIterator {
-    let time: TickTimer = Default::default();
-
-    let end_point = absolute_point_coord(current, abs, end_x, end_y);
-
-    // If the endpoints are identical, then this is equivalent to omitting the elliptical arc segment entirely.
-    if current == end_point {
-        return PointIterator::Empty(EmptyPointIterator { end: end_point });
-    }
-
-    // If rx = 0 or ry = 0 then this arc is treated as a straight line segment joining the endpoints.
-    if rx == 0. || ry == 0. {
-        return line_to(current, abs, end_x, end_y);
-    }
-
-    let (start_angle, sweep_angle, rx_abs, ry_abs, x_rad_rotation, center_x, center_y) =
-        ellipse_support_calc(
-            current,
-            rx,
-            ry,
-            x_axis_rotation,
-            large_arc,
-            sweep,
-            end_point.x,
-            end_point.y,
-        );
-
-    let calc_formula = EllipseCurve::new(
-        start_angle,
-        sweep_angle,
-        rx_abs,
-        ry_abs,
-        x_rad_rotation,
-        center_x,
-        center_y,
-    );
-    PointIterator::EllipseCurve(EllipsePointIterator {
-        time,
-        calc_formula,
-        end: end_point,
-    })
+struct CubicCurvePointIterator<S: Scalar> {
+    sampler: CurveSampler<S>,
+    calc_formula: CubicCurve<S>,
 }
 
-fn absolute_point_coord(start: Point, abs: bool, x: f64, y: f64) -> Point {
-    match abs {
-        true => Point { x, y },
-        false => Point { x, y } + start,
-    }
+// === === === ELLIPSE === === ===
+struct EllipsePointIterator<S: Scalar> {
+    sampler: CurveSampler<S>,
+    calc_formula: EllipseCurve<S>,
 }
 
-enum CurveType {
-    Cubic,
-    Quadratic,
+// === === === POINT ITERATOR === === ===
+enum PointIterator<S: Scalar> {
+    Line(LinePointIterator<S>),
+    SquareCurve(SquareCurvePointIterator<S>),
+    CubicCurve(CubicCurvePointIterator<S>),
+    EllipseCurve(EllipsePointIterator<S>),
 }
 
-fn path_command_condition(prev_support_point: &SupportPoint, curve_type: CurveType) -> bool {
-    match curve_type {
-        CurveType::Cubic => {
-            prev_support_point.path_command == PathCommand::SmoothCurveTo
-                || prev_support_point.path_command == PathCommand::CurveTo
+impl<S: Scalar> PointIterator<S> {
+    fn move_type(&self) -> MoveType {
+        match self {
+            PointIterator::Line(iter) => iter.move_type,
+            PointIterator::SquareCurve(_) => MoveType::Draw,
+            PointIterator::CubicCurve(_) => MoveType::Draw,
+            PointIterator::EllipseCurve(_) => MoveType::Draw,
         }
+    }
+}
 
-        CurveType::Quadratic => {
-            prev_support_point.path_command == PathCommand::SmoothQuadratic
-                || prev_support_point.path_command == PathCommand::Quadratic
+impl<S: Scalar> Iterator for PointIterator<S> {
+    type Item = Point<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            PointIterator::Line(iter) => iter.next(),
+            PointIterator::SquareCurve(iter) => iter.sampler.next_point(&iter.calc_formula),
+            PointIterator::CubicCurve(iter) => iter.sampler.next_point(&iter.calc_formula),
+            PointIterator::EllipseCurve(iter) => iter.sampler.next_point(&iter.calc_formula),
         }
     }
 }
 
-fn mirrored_point(
-    current: Point,
-    abs: bool,
-    prev_support_point_opt: Option<SupportPoint>,
-    curve_type: CurveType,
-) -> Point {
-    let mut mirrored_point = match prev_support_point_opt {
-        Some(ref prev_support_point) if path_command_condition(prev_support_point, curve_type) => {
-            current - prev_support_point.point
+fn calc_point_iterator<S: Scalar>(
+    primitive: Primitive,
+    options: FlattenOptions,
+) -> PointIterator<S> {
+    match primitive {
+        Primitive::Move { end } => {
+            PointIterator::Line(LinePointIterator::new(end, end, MoveType::Fly, options))
+        }
+        Primitive::Line { start, end } => {
+            PointIterator::Line(LinePointIterator::new(start, end, MoveType::Draw, options))
         }
-        _ => Point::ZERO,
-    };
+        Primitive::Quadratic { start, p1, end } => {
+            let p1_on_lane = is_point_on_lane(start, end, &p1, options.straight_tolerance);
+            if options.collapse_straight_curves && p1_on_lane {
+                return PointIterator::Line(LinePointIterator::new(
+                    start,
+                    end,
+                    MoveType::Draw,
+                    options,
+                ));
+            }
 
-    if abs {
-        mirrored_point = mirrored_point + current;
-    }
+            let calc_formula = SquareCurve::new(start.cast(), p1.cast(), end.cast());
+            let time = options.flattening.curve_timer(&calc_formula);
+            PointIterator::SquareCurve(SquareCurvePointIterator {
+                sampler: CurveSampler::new(time, start, end, options),
+                calc_formula,
+            })
+        }
+        Primitive::Cubic { start, p1, p2, end } => {
+            let p1_on_lane = is_point_on_lane(start, end, &p1, options.straight_tolerance);
+            let p2_on_lane = is_point_on_lane(start, end, &p2, options.straight_tolerance);
+            if options.collapse_straight_curves && p1_on_lane && p2_on_lane {
+                return PointIterator::Line(LinePointIterator::new(
+                    start,
+                    end,
+                    MoveType::Draw,
+                    options,
+                ));
+            }
 
-    mirrored_point
+            let calc_formula = CubicCurve::new(start.cast(), p1.cast(), p2.cast(), end.cast());
+            let time = options.flattening.curve_timer(&calc_formula);
+            PointIterator::CubicCurve(CubicCurvePointIterator {
+                sampler: CurveSampler::new(time, start, end, options),
+                calc_formula,
+            })
+        }
+        Primitive::Arc {
+            start,
+            end,
+            center,
+            rx,
+            ry,
+            x_rotation,
+            start_angle,
+            sweep_angle,
+            ..
+        } => {
+            let calc_formula = EllipseCurve::new(
+                S::from_f64(start_angle),
+                S::from_f64(sweep_angle),
+                S::from_f64(rx),
+                S::from_f64(ry),
+                S::from_f64(x_rotation),
+                S::from_f64(center.x),
+                S::from_f64(center.y),
+            );
+            let time = options.flattening.curve_timer(&calc_formula);
+            PointIterator::EllipseCurve(EllipsePointIterator {
+                sampler: CurveSampler::new(time, start, end, options),
+                calc_formula,
+            })
+        }
+    }
 }
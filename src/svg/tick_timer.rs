@@ -1,27 +1,50 @@
+use super::math::clamp_ticks;
+use crate::float::ceil;
+use crate::scalar::Scalar;
+
+/// Yields `1/ticks, 2/ticks, ..., 1.0`. The start of a curve is skipped because it is
+/// the end of the previous segment, and the last value is exactly `1.0`.
 pub struct TickTimer {
-    pub time: f64,
+    ticks: usize,
+    tick: usize,
 }
 
-impl Default for TickTimer {
-    fn default() -> Self {
-        TickTimer { time: 0.0 }
+impl TickTimer {
+    pub fn with_period(period: f64) -> Self {
+        TickTimer::with_ticks(clamp_ticks(ceil(1.0 / period)))
     }
-}
 
-impl TickTimer {
-    const TICK_PERIOD: f64 = 0.001; //todo: number of ticks should be calculated based on curve length
+    pub fn with_ticks(ticks: usize) -> Self {
+        TickTimer { ticks, tick: 0 }
+    }
+
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.ticks
+    }
+
+    /// Next time in any scalar type; the division is done in that type.
+    pub fn next_time<S: Scalar>(&mut self) -> Option<S> {
+        if self.is_finished() {
+            None
+        } else {
+            self.tick += 1;
+            if self.tick == self.ticks {
+                Some(S::ONE)
+            } else {
+                Some(S::from_ratio(self.tick, self.ticks))
+            }
+        }
+    }
 }
 
 impl Iterator for TickTimer {
     type Item = f64;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.time > 1.0 {
-            None
-        } else {
-            let current_value = self.time;
-            self.time += TickTimer::TICK_PERIOD;
-            Some(current_value)
-        }
+        self.next_time()
     }
 }
@@ -0,0 +1,155 @@
+use crate::float::{atan2, cos, sin, sqrt, tan};
+use core::f64::consts::PI;
+
+use super::point::Point;
+
+/// Affine transform in SVG matrix order: `x' = a*x + c*y + e`, `y' = b*x + d*y + f`.
+/// Angles are in degrees, as in the SVG `transform` attribute.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.,
+        b: 0.,
+        c: 0.,
+        d: 1.,
+        e: 0.,
+        f: 0.,
+    };
+
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Transform { a, b, c, d, e, f }
+    }
+
+    pub fn translate(tx: f64, ty: f64) -> Self {
+        Transform::new(1., 0., 0., 1., tx, ty)
+    }
+
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Transform::new(sx, 0., 0., sy, 0., 0.)
+    }
+
+    /// Rotation about the origin; positive angles turn clockwise on screen.
+    pub fn rotate(degrees: f64) -> Self {
+        let radians = degrees * PI / 180.;
+        let (sin, cos) = (sin(radians), cos(radians));
+        Transform::new(cos, sin, -sin, cos, 0., 0.)
+    }
+
+    pub fn skew_x(degrees: f64) -> Self {
+        Transform::new(1., 0., tan(degrees * PI / 180.), 1., 0., 0.)
+    }
+
+    pub fn skew_y(degrees: f64) -> Self {
+        Transform::new(1., tan(degrees * PI / 180.), 0., 1., 0., 0.)
+    }
+
+    /// This transform followed by `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    pub fn apply(&self, point: Point) -> Point {
+        Point::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    /// The transform that undoes this one, `None` if it squashes the plane onto a line
+    /// or a point.
+    pub fn inverse(&self) -> Option<Transform> {
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant == 0. || !determinant.is_finite() {
+            return None;
+        }
+        Some(Transform {
+            a: self.d / determinant,
+            b: -self.b / determinant,
+            c: -self.c / determinant,
+            d: self.a / determinant,
+            e: (self.c * self.f - self.d * self.e) / determinant,
+            f: (self.b * self.e - self.a * self.f) / determinant,
+        })
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::IDENTITY
+    }
+
+    /// The image of the ellipse arc `(rx, ry, x_rotation, start_angle, sweep_angle)`,
+    /// all angles in radians, as the same five parameters. The image of an ellipse is
+    /// again an ellipse; its axes come from the singular value decomposition of the
+    /// linear part.
+    pub(crate) fn ellipse_arc(
+        &self,
+        rx: f64,
+        ry: f64,
+        x_rotation: f64,
+        start_angle: f64,
+        sweep_angle: f64,
+    ) -> (f64, f64, f64, f64, f64) {
+        // maps (cos t, sin t) of the untransformed ellipse to the transformed one
+        let (rotation_sin, rotation_cos) = (sin(x_rotation), cos(x_rotation));
+        let m00 = (self.a * rotation_cos + self.c * rotation_sin) * rx;
+        let m01 = (self.c * rotation_cos - self.a * rotation_sin) * ry;
+        let m10 = (self.b * rotation_cos + self.d * rotation_sin) * rx;
+        let m11 = (self.d * rotation_cos - self.b * rotation_sin) * ry;
+
+        // m = rotate(phi) * scale(first, second) * rotate(theta)
+        let e = (m00 + m11) / 2.;
+        let f = (m00 - m11) / 2.;
+        let g = (m10 + m01) / 2.;
+        let h = (m10 - m01) / 2.;
+        let q = sqrt(e * e + h * h);
+        let r = sqrt(f * f + g * g);
+        let first = q + r;
+        let second = q - r;
+        let a1 = atan2(g, f);
+        let a2 = atan2(h, e);
+        let theta = (a2 - a1) / 2.;
+        let phi = (a2 + a1) / 2.;
+
+        // a mirroring transform reverses the direction the arc is walked in
+        if second < 0. {
+            (first, -second, phi, -(start_angle + theta), -sweep_angle)
+        } else {
+            (first, second, phi, start_angle + theta, sweep_angle)
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+#[cfg(feature = "svgtypes")]
+impl From<svgtypes::Transform> for Transform {
+    fn from(transform: svgtypes::Transform) -> Self {
+        Transform::new(
+            transform.a,
+            transform.b,
+            transform.c,
+            transform.d,
+            transform.e,
+            transform.f,
+        )
+    }
+}
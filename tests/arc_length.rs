@@ -0,0 +1,63 @@
+use svg_to_lines::svg::flatten_options::{FlattenOptions, Flattening};
+use svg_to_lines::svg::math::distance;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::svg_curve::points_from_path_segments_with_options;
+use svgtypes::PathParser;
+
+fn chord_lengths(path: &str, spacing: f64) -> Vec<f64> {
+    let options = FlattenOptions {
+        flattening: Flattening::ArcLength(spacing),
+        ..Default::default()
+    };
+    let segments = PathParser::from(path).map(|segment| segment.unwrap());
+    let points: Vec<Point> = points_from_path_segments_with_options(segments, options)
+        .map(|line_to| line_to.target())
+        .collect();
+    points
+        .windows(2)
+        .map(|pair| distance(pair[0], pair[1]))
+        .collect()
+}
+
+fn assert_evenly_spaced(path: &str, spacing: f64) {
+    let lengths = chord_lengths(path, spacing);
+    assert!(
+        lengths.len() > 10,
+        "{}: only {} chords",
+        path,
+        lengths.len()
+    );
+    for length in lengths {
+        // the spacing only shrinks, to fit a whole number of steps, and chords are a
+        // little shorter than the curve they cut across
+        assert!(
+            length > 0.8 * spacing && length <= spacing * 1.001,
+            "{}: chord of {} for a spacing of {}",
+            path,
+            length,
+            spacing
+        );
+    }
+}
+
+#[test]
+fn cubic_points_are_evenly_spaced() {
+    // slow at the ends and fast in the middle in parameter time
+    assert_evenly_spaced("M0,0 C0,0 100,100 100,100", 2.);
+    assert_evenly_spaced("M0,0 C10,90 90,-60 100,30", 2.);
+}
+
+#[test]
+fn quadratic_points_are_evenly_spaced() {
+    assert_evenly_spaced("M0,0 Q95,0 100,100", 1.5);
+}
+
+#[test]
+fn arc_points_are_evenly_spaced() {
+    assert_evenly_spaced("M0,0 A60,20 30 0,1 100,40", 2.);
+}
+
+#[test]
+fn line_points_are_evenly_spaced() {
+    assert_evenly_spaced("M0,0 L100,20 H40", 3.);
+}
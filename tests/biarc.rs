@@ -0,0 +1,220 @@
+use std::f64::consts::PI;
+
+use svg_to_lines::svg::biarc::arcs_from_path_segments;
+use svg_to_lines::svg::math::{
+    angle_around, arc_sweep, distance, distance_to_segment, CubicCurve, CurvePoint, EllipseCurve,
+    SquareCurve,
+};
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::svg_curve::{CircularArc, LineTo};
+use svgtypes::PathParser;
+
+const CURVE_SAMPLES: usize = 4000;
+const PIECE_SAMPLES: usize = 32;
+
+/// A line or circular arc of the output, with the point it starts at.
+struct Piece {
+    start: Point,
+    line_to: LineTo,
+}
+
+impl Piece {
+    // start angle and signed sweep, growing angles being clockwise with y pointing down
+    fn angles(&self, arc: &CircularArc) -> (f64, f64) {
+        let start_angle = angle_around(arc.center, self.start);
+        let sweep = arc_sweep(
+            angle_around(arc.center, arc.end) - start_angle,
+            arc.clockwise,
+        );
+        (start_angle, sweep)
+    }
+
+    fn at(&self, fraction: f64) -> Point {
+        match self.line_to {
+            LineTo::Arc(arc) => {
+                let (start_angle, sweep) = self.angles(&arc);
+                let angle = start_angle + sweep * fraction;
+                Point::new(
+                    arc.center.x + arc.radius * angle.cos(),
+                    arc.center.y + arc.radius * angle.sin(),
+                )
+            }
+            line_to => self.start + (line_to.target() - self.start) * fraction,
+        }
+    }
+
+    fn distance_to(&self, point: Point) -> f64 {
+        match self.line_to {
+            LineTo::Arc(arc) => {
+                let (start_angle, sweep) = self.angles(&arc);
+                let offset =
+                    arc_sweep(angle_around(arc.center, point) - start_angle, arc.clockwise);
+                if offset.abs() <= sweep.abs() {
+                    (distance(arc.center, point) - arc.radius).abs()
+                } else {
+                    distance(self.start, point).min(distance(arc.end, point))
+                }
+            }
+            line_to => distance_to_segment(self.start, line_to.target(), point),
+        }
+    }
+}
+
+fn pieces(path: &str, tolerance: f64) -> Vec<Piece> {
+    let segments = PathParser::from(path).map(|segment| segment.unwrap());
+    let mut pen = Point::ZERO;
+    let mut pieces = Vec::new();
+    for line_to in arcs_from_path_segments(segments, tolerance) {
+        if !matches!(line_to, LineTo::Fly(_)) {
+            pieces.push(Piece {
+                start: pen,
+                line_to,
+            });
+        }
+        pen = line_to.target();
+    }
+    pieces
+}
+
+// both ways round: every point of the output near the curve, every point of the curve
+// near the output
+fn assert_within_tolerance(path: &str, curve: &impl CurvePoint, tolerance: f64) {
+    let curve_points: Vec<Point> = (0..=CURVE_SAMPLES)
+        .map(|sample| curve.at(sample as f64 / CURVE_SAMPLES as f64))
+        .collect();
+    let pieces = pieces(path, tolerance);
+    let allowed = tolerance * 1.02;
+
+    let output_error = pieces
+        .iter()
+        .flat_map(|piece| {
+            (0..=PIECE_SAMPLES).map(move |sample| piece.at(sample as f64 / PIECE_SAMPLES as f64))
+        })
+        .map(|point| {
+            curve_points
+                .windows(2)
+                .map(|chord| distance_to_segment(chord[0], chord[1], point))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .fold(0., f64::max);
+    assert!(
+        output_error <= allowed,
+        "{} at {}: output {} away from the curve",
+        path,
+        tolerance,
+        output_error
+    );
+    let curve_error = curve_points
+        .iter()
+        .map(|point| {
+            pieces
+                .iter()
+                .map(|piece| piece.distance_to(*point))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .fold(0., f64::max);
+    assert!(
+        curve_error <= allowed,
+        "{} at {}: curve {} away from the output",
+        path,
+        tolerance,
+        curve_error
+    );
+}
+
+#[test]
+fn quadratic_biarcs_stay_within_tolerance() {
+    let curve = SquareCurve::new(
+        Point::new(0., 0.),
+        Point::new(50., 80.),
+        Point::new(100., 0.),
+    );
+    for tolerance in [1., 0.1, 0.01] {
+        assert_within_tolerance("M0,0 Q50,80 100,0", &curve, tolerance);
+    }
+}
+
+#[test]
+fn cubic_biarcs_stay_within_tolerance() {
+    // an S bend, with an inflection in the middle
+    let s_bend = CubicCurve::new(
+        Point::new(0., 0.),
+        Point::new(10., 90.),
+        Point::new(90., -60.),
+        Point::new(100., 30.),
+    );
+    // a tight loop near the end
+    let loop_curve = CubicCurve::new(
+        Point::new(0., 0.),
+        Point::new(100., 0.),
+        Point::new(0., 40.),
+        Point::new(60., -10.),
+    );
+    for tolerance in [1., 0.1, 0.01] {
+        assert_within_tolerance("M0,0 C10,90 90,-60 100,30", &s_bend, tolerance);
+        assert_within_tolerance("M0,0 C100,0 0,40 60,-10", &loop_curve, tolerance);
+    }
+}
+
+#[test]
+fn elliptical_arc_biarcs_stay_within_tolerance() {
+    // the lower half of an ellipse around the origin, y pointing down
+    let curve = EllipseCurve::new(0., PI, 60., 20., 0., 0., 0.);
+    for tolerance in [1., 0.1, 0.01] {
+        assert_within_tolerance("M60,0 A60,20 0 0,1 -60,0", &curve, tolerance);
+    }
+}
+
+#[test]
+fn arc_sweeps_follow_the_direction_and_close_to_full_circles() {
+    let assert_near = |sweep: f64, expected: f64| {
+        assert!(
+            (sweep - expected).abs() < 1e-12,
+            "{} instead of {}",
+            sweep,
+            expected
+        )
+    };
+    assert_near(arc_sweep(PI / 2., true), PI / 2.);
+    assert_near(arc_sweep(PI / 2., false), -1.5 * PI);
+    assert_near(arc_sweep(-PI / 2., true), 1.5 * PI);
+    assert_near(arc_sweep(-1.5 * PI, false), -1.5 * PI);
+    assert_near(arc_sweep(2. * PI, true), 2. * PI);
+    // an arc ending where it starts is a whole turn either way
+    assert_near(arc_sweep(0., true), 2. * PI);
+    assert_near(arc_sweep(0., false), -2. * PI);
+}
+
+#[test]
+fn curves_past_the_depth_limit_keep_their_biarcs() {
+    // nothing meets a zero tolerance, so every curve is halved as far as it goes and the
+    // biarcs of the last halves are kept instead of chords
+    let pieces = pieces("M0,0 Q50,80 100,0", 0.);
+    let arcs = pieces
+        .iter()
+        .filter(|piece| matches!(piece.line_to, LineTo::Arc(_)))
+        .count();
+    assert!(pieces.len() <= 2 << 16, "{}", pieces.len());
+    assert!(arcs > pieces.len() / 2, "{} of {}", arcs, pieces.len());
+    assert_eq!(
+        pieces.last().map(|piece| piece.line_to.target()),
+        Some(Point::new(100., 0.))
+    );
+    // and they still follow the curve closely
+    let curve = SquareCurve::new(
+        Point::new(0., 0.),
+        Point::new(50., 80.),
+        Point::new(100., 0.),
+    );
+    let curve_points: Vec<Point> = (0..=CURVE_SAMPLES)
+        .map(|sample| curve.at(sample as f64 / CURVE_SAMPLES as f64))
+        .collect();
+    for piece in pieces.iter().step_by(997) {
+        let middle = piece.at(0.5);
+        let nearest = curve_points
+            .windows(2)
+            .map(|chord| distance_to_segment(chord[0], chord[1], middle))
+            .fold(f64::INFINITY, f64::min);
+        assert!(nearest < 1e-3, "{:?} is {} away", middle, nearest);
+    }
+}
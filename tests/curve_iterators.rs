@@ -0,0 +1,138 @@
+use proptest::prelude::*;
+use svg_to_lines::svg::flatten_options::{FlattenOptions, Flattening};
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::primitive::primitives_from_path_segments;
+use svg_to_lines::svg::svg_curve::{points_from_path_segments_with_options, LineTo};
+use svgtypes::{PathParser, PathSegment};
+
+fn coord() -> impl Strategy<Value = f64> {
+    // whole and half units make coincident points (and so duplicates) likely
+    prop_oneof![(-20i32..20).prop_map(|v| v as f64 / 2.), -100.0..100.0f64]
+}
+
+fn path_segment() -> impl Strategy<Value = PathSegment> {
+    prop_oneof![
+        (any::<bool>(), coord(), coord()).prop_map(|(abs, x, y)| PathSegment::MoveTo { abs, x, y }),
+        (any::<bool>(), coord(), coord()).prop_map(|(abs, x, y)| PathSegment::LineTo { abs, x, y }),
+        (any::<bool>(), coord()).prop_map(|(abs, x)| PathSegment::HorizontalLineTo { abs, x }),
+        (any::<bool>(), coord()).prop_map(|(abs, y)| PathSegment::VerticalLineTo { abs, y }),
+        (
+            any::<bool>(),
+            coord(),
+            coord(),
+            coord(),
+            coord(),
+            coord(),
+            coord()
+        )
+            .prop_map(|(abs, x1, y1, x2, y2, x, y)| PathSegment::CurveTo {
+                abs,
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y
+            }),
+        (any::<bool>(), coord(), coord(), coord(), coord())
+            .prop_map(|(abs, x2, y2, x, y)| PathSegment::SmoothCurveTo { abs, x2, y2, x, y }),
+        (any::<bool>(), coord(), coord(), coord(), coord())
+            .prop_map(|(abs, x1, y1, x, y)| PathSegment::Quadratic { abs, x1, y1, x, y }),
+        (any::<bool>(), coord(), coord()).prop_map(|(abs, x, y)| PathSegment::SmoothQuadratic {
+            abs,
+            x,
+            y
+        }),
+        (
+            any::<bool>(),
+            coord(),
+            coord(),
+            coord(),
+            any::<bool>(),
+            any::<bool>(),
+            coord(),
+            coord()
+        )
+            .prop_map(|(abs, rx, ry, x_axis_rotation, large_arc, sweep, x, y)| {
+                PathSegment::EllipticalArc {
+                    abs,
+                    rx,
+                    ry,
+                    x_axis_rotation,
+                    large_arc,
+                    sweep,
+                    x,
+                    y,
+                }
+            }),
+        any::<bool>().prop_map(|abs| PathSegment::ClosePath { abs }),
+    ]
+}
+
+fn flatten_options() -> impl Strategy<Value = FlattenOptions> {
+    let flattening = prop_oneof![
+        (0.01..0.5f64).prop_map(Flattening::FixedStep),
+        (0.001..2.0f64).prop_map(Flattening::Tolerance),
+        (0.1..5.0f64).prop_map(Flattening::ArcLength),
+    ];
+    (flattening, 0.0..1.0f64, any::<bool>()).prop_map(
+        |(flattening, min_segment_length, collapse_straight_curves)| FlattenOptions {
+            flattening,
+            min_segment_length,
+            collapse_straight_curves,
+            ..Default::default()
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn every_segment_ends_on_its_end_position(
+        segments in prop::collection::vec(path_segment(), 1..8),
+        options in flatten_options(),
+    ) {
+        for prefix in 1..=segments.len() {
+            let path = &segments[..prefix];
+            let end_position = primitives_from_path_segments(path.iter().cloned())
+                .last()
+                .map(|primitive| primitive.end())
+                .unwrap_or(Point::ZERO);
+            let last_point = points_from_path_segments_with_options(path.iter().cloned(), options)
+                .last()
+                .map(|line_to| line_to.target())
+                .unwrap_or(Point::ZERO);
+
+            prop_assert_eq!(last_point, end_position);
+        }
+    }
+
+    #[test]
+    fn no_point_repeats_the_previous_one(
+        segments in prop::collection::vec(path_segment(), 1..8),
+        options in flatten_options(),
+    ) {
+        // the pen starts at the origin, but where it really is only the first `Fly` knows
+        let mut previous: Option<Point> = None;
+        for line_to in points_from_path_segments_with_options(segments.into_iter(), options) {
+            if previous.is_some() || !matches!(line_to, LineTo::Fly(_)) {
+                prop_assert_ne!(line_to.target(), previous.unwrap_or(Point::ZERO));
+            }
+            previous = Some(line_to.target());
+        }
+    }
+}
+
+#[test]
+fn moves_to_the_pen_position_are_dropped() {
+    let segments = PathParser::from("M1,1 L5,5 M5,5 M5,5 L6,6").map(|segment| segment.unwrap());
+    let line_tos: Vec<LineTo> =
+        points_from_path_segments_with_options(segments, Default::default()).collect();
+    assert_eq!(
+        line_tos,
+        vec![
+            LineTo::Fly(Point::new(1., 1.)),
+            LineTo::Draw(Point::new(5., 5.)),
+            LineTo::Draw(Point::new(6., 6.)),
+        ]
+    );
+}
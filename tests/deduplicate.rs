@@ -0,0 +1,129 @@
+use svg_to_lines::svg::deduplicate::{deduplicate_line_tos, deduplicate_sub_paths};
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::sub_path::{sub_paths, SubPath};
+use svg_to_lines::svg::svg_curve::points_from_path_segments;
+use svgtypes::PathParser;
+
+fn sub_path(points: &[(f64, f64)]) -> SubPath {
+    SubPath {
+        points: points.iter().map(|&(x, y)| Point::new(x, y)).collect(),
+    }
+}
+
+fn shapes(path: &str) -> Vec<SubPath> {
+    let segments = PathParser::from(path).map(|segment| segment.unwrap());
+    sub_paths(points_from_path_segments(segments))
+}
+
+fn drawn_length(sub_paths: &[SubPath]) -> f64 {
+    sub_paths
+        .iter()
+        .flat_map(|sub_path| sub_path.points.windows(2))
+        .map(|ends| {
+            let offset = ends[1] - ends[0];
+            offset.x.hypot(offset.y)
+        })
+        .sum()
+}
+
+#[test]
+fn a_shared_edge_is_drawn_once() {
+    // two squares side by side share the edge at x = 10, drawn downwards by the second
+    let deduplicated = deduplicate_sub_paths(shapes("M0,0 H10 V10 H0 Z M10,10 V0 H20 V10 Z"), 1e-9);
+    assert_eq!(drawn_length(&deduplicated), 70.);
+    assert_eq!(
+        deduplicated,
+        vec![
+            sub_path(&[(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 0.)]),
+            sub_path(&[(10., 0.), (20., 0.), (20., 10.), (10., 10.)]),
+        ]
+    );
+}
+
+#[test]
+fn partial_overlaps_keep_the_new_part() {
+    let deduplicated = deduplicate_sub_paths(
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(5., 0.), (15., 0.), (15., 5.)]),
+        ],
+        1e-9,
+    );
+    assert_eq!(
+        deduplicated,
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(10., 0.), (15., 0.), (15., 5.)]),
+        ]
+    );
+}
+
+#[test]
+fn the_middle_of_a_stroke_is_cut_out() {
+    let deduplicated = deduplicate_sub_paths(
+        vec![
+            sub_path(&[(4., 0.), (6., 0.)]),
+            sub_path(&[(0., 0.), (10., 0.)]),
+        ],
+        1e-9,
+    );
+    assert_eq!(
+        deduplicated,
+        vec![
+            sub_path(&[(4., 0.), (6., 0.)]),
+            sub_path(&[(0., 0.), (4., 0.)]),
+            sub_path(&[(6., 0.), (10., 0.)]),
+        ]
+    );
+}
+
+#[test]
+fn near_lines_within_the_tolerance_count_as_drawn() {
+    let pieces = vec![
+        sub_path(&[(0., 0.), (10., 0.)]),
+        sub_path(&[(0., 0.05), (10., 0.05)]),
+    ];
+    assert_eq!(deduplicate_sub_paths(pieces.clone(), 0.01), pieces);
+    assert_eq!(deduplicate_sub_paths(pieces.clone(), 0.1), pieces[..1]);
+}
+
+#[test]
+fn crossings_and_short_leftovers() {
+    // crossing lines share a point, not a stretch
+    let crossing = vec![
+        sub_path(&[(0., 0.), (10., 10.)]),
+        sub_path(&[(0., 10.), (10., 0.)]),
+    ];
+    assert_eq!(deduplicate_sub_paths(crossing.clone(), 0.1), crossing);
+
+    // what sticks out of the drawn line by no more than the tolerance is dropped too
+    let deduplicated = deduplicate_sub_paths(
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(0., 0.), (10.05, 0.)]),
+        ],
+        0.1,
+    );
+    assert_eq!(deduplicated, vec![sub_path(&[(0., 0.), (10., 0.)])]);
+}
+
+#[test]
+fn deduplicated_line_tos_fly_over_the_cut() {
+    let line_tos = vec![
+        LineTo::Fly(Point::new(0., 0.)),
+        LineTo::Draw(Point::new(10., 0.)),
+        LineTo::Fly(Point::new(10., 0.)),
+        LineTo::Draw(Point::new(0., 0.)),
+        LineTo::Draw(Point::new(0., 10.)),
+    ];
+    assert_eq!(
+        deduplicate_line_tos(line_tos, 1e-9),
+        vec![
+            LineTo::Fly(Point::new(0., 0.)),
+            LineTo::Draw(Point::new(10., 0.)),
+            LineTo::Fly(Point::new(0., 0.)),
+            LineTo::Draw(Point::new(0., 10.)),
+        ]
+    );
+}
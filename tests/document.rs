@@ -0,0 +1,248 @@
+use svg_to_lines::svg::document::{paths_from_document, points_from_document, DrawablePath};
+use svg_to_lines::svg::fill::{hatch, FillRule, HatchOptions};
+use svg_to_lines::svg::flatten_options::FlattenOptions;
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::sub_path::sub_paths;
+use svg_to_lines::svg::svg_curve::points_from_path_segments_with_options;
+use svg_to_lines::svg::transform::Transform;
+
+const LINE: &str = r#"d="M0,0 L10,0""#;
+
+fn paths(body: &str) -> Vec<DrawablePath> {
+    let text = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+                xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd">{}</svg>"#,
+        body
+    );
+    paths_from_document(&text).unwrap()
+}
+
+// elements whose outline is drawn
+fn count(body: &str) -> usize {
+    paths(&body.replace("LINE", LINE))
+        .iter()
+        .filter(|path| path.stroke)
+        .count()
+}
+
+#[test]
+fn stroke_is_inherited_and_overridden() {
+    assert_eq!(count(r#"<path LINE/>"#), 0);
+    assert_eq!(count(r#"<g stroke="black"><path LINE/></g>"#), 1);
+    assert_eq!(
+        count(r#"<g stroke="black"><path stroke="none" LINE/></g>"#),
+        0
+    );
+    assert_eq!(
+        count(r#"<g stroke="black"><g stroke="inherit"><path LINE/></g></g>"#),
+        1
+    );
+    // the style attribute wins over presentation attributes
+    assert_eq!(
+        count(r#"<path stroke="none" style="stroke: red" LINE/>"#),
+        1
+    );
+    assert_eq!(count(r#"<path stroke="red" style="stroke:none" LINE/>"#), 0);
+    assert_eq!(
+        count(r#"<g stroke="red" stroke-width="0"><path LINE/></g>"#),
+        0
+    );
+    assert_eq!(
+        count(r#"<g stroke="red" stroke-width="0"><path stroke-width="2" LINE/></g>"#),
+        1
+    );
+}
+
+#[test]
+fn hidden_elements_are_skipped() {
+    assert_eq!(
+        count(r#"<g stroke="red" display="none"><path LINE/></g>"#),
+        0
+    );
+    assert_eq!(
+        count(r#"<g stroke="red"><path style="display:none" LINE/></g>"#),
+        0
+    );
+    // visibility is inherited, and a child can turn itself visible again
+    assert_eq!(
+        count(
+            r#"<g stroke="red" visibility="hidden"><path LINE/><path visibility="visible" LINE/></g>"#
+        ),
+        1
+    );
+    assert_eq!(count(r#"<g stroke="red" opacity="0"><path LINE/></g>"#), 0);
+    assert_eq!(count(r#"<g stroke="red" opacity="0%"><path LINE/></g>"#), 0);
+    assert_eq!(
+        count(r#"<g stroke="red" opacity="50%"><path LINE/></g>"#),
+        1
+    );
+    assert_eq!(count(r#"<path stroke="red" stroke-opacity="0" LINE/>"#), 0);
+    // templates are only drawn when referenced
+    assert_eq!(count(r#"<defs><path stroke="red" LINE/></defs>"#), 0);
+    assert_eq!(count(r#"<symbol><path stroke="red" LINE/></symbol>"#), 0);
+}
+
+#[test]
+fn foreign_elements_are_skipped() {
+    assert_eq!(
+        count(r#"<sodipodi:namedview><path stroke="red" LINE/></sodipodi:namedview>"#),
+        0
+    );
+    assert_eq!(
+        count(r#"<g xmlns:other="urn:other"><other:path stroke="red" LINE/></g>"#),
+        0
+    );
+    // foreign attributes do not style the element
+    assert_eq!(count(r#"<path sodipodi:stroke="red" LINE/>"#), 0);
+}
+
+#[test]
+fn use_draws_the_referenced_element_moved_by_x_and_y() {
+    for href in ["href", "xlink:href"] {
+        let paths = paths(&format!(
+            r##"<defs><path id="line" {}/></defs>
+               <use {}="#line" x="5" y="7" stroke="red" transform="scale(2)"/>"##,
+            LINE, href
+        ));
+        assert_eq!(paths.len(), 1, "{}", href);
+        // moved first, then scaled
+        assert_eq!(
+            paths[0].transform.apply(Point::new(10., 0.)),
+            Point::new(30., 14.)
+        );
+    }
+}
+
+#[test]
+fn use_passes_its_style_on() {
+    assert_eq!(
+        count(r##"<defs><path id="line" LINE/></defs><use href="#line"/>"##),
+        0
+    );
+    assert_eq!(
+        count(r##"<defs><path id="line" LINE/></defs><g stroke="red"><use href="#line"/></g>"##),
+        1
+    );
+    // the referenced element's own style still applies
+    assert_eq!(
+        count(
+            r##"<defs><path id="line" stroke="none" LINE/></defs><use href="#line" stroke="red"/>"##
+        ),
+        0
+    );
+    assert_eq!(
+        count(
+            r##"<defs><path id="line" LINE/></defs><use href="#line" stroke="red" display="none"/>"##
+        ),
+        0
+    );
+}
+
+#[test]
+fn use_of_symbols_and_groups() {
+    let paths = paths(&format!(
+        r##"<symbol id="mark"><path {0}/><path transform="translate(0,5)" {0}/></symbol>
+            <g id="group" stroke="red"><path {0}/></g>
+            <use href="#mark" stroke="red"/>
+            <use href="#group" y="10"/>"##,
+        LINE
+    ));
+    assert_eq!(paths.len(), 4);
+    assert_eq!(paths[3].transform, Transform::translate(0., 10.));
+}
+
+#[test]
+fn broken_references_draw_nothing() {
+    assert_eq!(count(r##"<use href="#missing" stroke="red"/>"##), 0);
+    assert_eq!(count(r##"<use href="line" stroke="red"/>"##), 0);
+    // the group is drawn once itself and once through the reference, which stops there
+    assert_eq!(
+        count(r##"<g id="loop" stroke="red"><path LINE/><use href="#loop"/></g>"##),
+        2
+    );
+    assert_eq!(
+        count(r##"<g stroke="red"><use id="a" href="#b"/><use id="b" href="#a"/></g>"##),
+        0
+    );
+}
+
+#[test]
+fn fills_are_black_unless_turned_off() {
+    let fills = |body: &str| -> Vec<Option<FillRule>> {
+        paths(&body.replace("LINE", LINE))
+            .iter()
+            .map(|path| path.fill)
+            .collect()
+    };
+    assert_eq!(fills(r#"<path LINE/>"#), vec![Some(FillRule::NonZero)]);
+    assert_eq!(fills(r#"<path fill="none" LINE/>"#), vec![]);
+    assert_eq!(fills(r#"<g fill="none"><path LINE/></g>"#), vec![]);
+    assert_eq!(
+        fills(r#"<g fill="none"><path style="fill: #123" LINE/></g>"#),
+        vec![Some(FillRule::NonZero)]
+    );
+    assert_eq!(fills(r#"<path fill-opacity="0" LINE/>"#), vec![]);
+    assert_eq!(fills(r#"<path visibility="hidden" LINE/>"#), vec![]);
+    // the rule is inherited like the paint
+    assert_eq!(
+        fills(r#"<g fill-rule="evenodd"><path LINE/><path fill-rule="nonzero" LINE/></g>"#),
+        vec![Some(FillRule::EvenOdd), Some(FillRule::NonZero)]
+    );
+    // lines have no inside
+    assert_eq!(fills(r#"<line x2="10"/>"#), vec![]);
+
+    // a stroke without a fill, and both
+    let paths = paths(
+        r#"<path stroke="red" fill="none" d="M0,0 H10"/><rect stroke="red" width="5" height="5"/>"#,
+    );
+    assert_eq!(
+        paths
+            .iter()
+            .map(|path| (path.stroke, path.fill))
+            .collect::<Vec<_>>(),
+        vec![(true, None), (true, Some(FillRule::NonZero))]
+    );
+}
+
+#[test]
+fn filled_elements_can_be_hatched() {
+    let text = r#"<svg xmlns="http://www.w3.org/2000/svg">
+        <rect x="10" y="10" width="20" height="20" fill-rule="evenodd"/>
+    </svg>"#;
+    // only outlines are flattened, and this one is not stroked
+    assert_eq!(
+        points_from_document(text, Default::default()).unwrap(),
+        vec![]
+    );
+
+    let paths = paths_from_document(text).unwrap();
+    let path = &paths[0];
+    let outline = points_from_path_segments_with_options(
+        path.segments.iter().copied(),
+        FlattenOptions {
+            transform: path.transform,
+            ..Default::default()
+        },
+    );
+    let lines = hatch(
+        &sub_paths(outline),
+        HatchOptions {
+            angle: 0.,
+            spacing: 2.,
+            cross_hatch: false,
+            fill_rule: path.fill.unwrap(),
+        },
+    );
+    let draws: Vec<Point> = lines
+        .iter()
+        .filter_map(|line_to| match line_to {
+            LineTo::Draw(point) => Some(*point),
+            _ => None,
+        })
+        .collect();
+    assert!(draws.len() >= 9, "{:?}", lines);
+    assert!(draws
+        .iter()
+        .all(|point| (10. ..=30.).contains(&point.x) && (10. ..=30.).contains(&point.y)));
+}
@@ -0,0 +1,218 @@
+use svg_to_lines::svg::fill::{concentric, hatch, spiral, FillRule, HatchOptions, OffsetOptions};
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::math::distance;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::sub_path::{sub_paths, SubPath};
+use svg_to_lines::svg::svg_curve::points_from_path_segments;
+use svgtypes::PathParser;
+
+// a 10 x 10 square with a 4 x 4 square in the middle, drawn the same way round or not
+const SAME_WINDING: &str = "M0,0 H10 V10 H0 Z M3,3 H7 V7 H3 Z";
+const OPPOSITE_WINDING: &str = "M0,0 H10 V10 H0 Z M3,3 V7 H7 V3 Z";
+
+fn shape(path: &str) -> Vec<SubPath> {
+    sub_paths(points_from_path_segments(
+        PathParser::from(path).map(|segment| segment.unwrap()),
+    ))
+}
+
+fn horizontal(fill_rule: FillRule) -> HatchOptions {
+    HatchOptions {
+        angle: 0.,
+        spacing: 1.,
+        cross_hatch: false,
+        fill_rule,
+    }
+}
+
+// the hatch lines as (start, end) pairs
+fn lines(line_tos: &[LineTo]) -> Vec<(Point, Point)> {
+    assert_eq!(line_tos.len() % 2, 0);
+    line_tos
+        .chunks(2)
+        .map(|pair| match *pair {
+            [LineTo::Fly(start), LineTo::Draw(end)] => (start, end),
+            _ => panic!("not a Fly, Draw pair: {:?}", pair),
+        })
+        .collect()
+}
+
+// the hatched x ranges on the line at `y`, left to right
+fn ranges_at(lines: &[(Point, Point)], y: f64) -> Vec<(f64, f64)> {
+    let mut ranges: Vec<(f64, f64)> = lines
+        .iter()
+        .filter(|(start, _)| start.y == y)
+        .map(|(start, end)| (start.x.min(end.x), start.x.max(end.x)))
+        .collect();
+    ranges.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    ranges
+}
+
+#[test]
+fn lines_sit_half_a_spacing_off_the_grid() {
+    let lines = lines(&hatch(
+        &shape("M0,0 H10 V10 H0 Z"),
+        horizontal(FillRule::NonZero),
+    ));
+    assert_eq!(lines.len(), 10);
+    for (index, (start, end)) in lines.iter().enumerate() {
+        assert_eq!(start.y, index as f64 + 0.5);
+        assert_eq!(end.y, start.y);
+        // every other line runs back
+        let expected = if index % 2 == 0 { (0., 10.) } else { (10., 0.) };
+        assert_eq!((start.x, end.x), expected);
+    }
+}
+
+#[test]
+fn nonzero_fills_a_hole_with_the_same_winding() {
+    let lines = lines(&hatch(&shape(SAME_WINDING), horizontal(FillRule::NonZero)));
+    assert_eq!(ranges_at(&lines, 5.5), vec![(0., 10.)]);
+    assert_eq!(ranges_at(&lines, 1.5), vec![(0., 10.)]);
+}
+
+#[test]
+fn evenodd_leaves_every_nested_sub_path_empty() {
+    for path in [SAME_WINDING, OPPOSITE_WINDING] {
+        let lines = lines(&hatch(&shape(path), horizontal(FillRule::EvenOdd)));
+        assert_eq!(
+            ranges_at(&lines, 5.5),
+            vec![(0., 3.), (7., 10.)],
+            "{}",
+            path
+        );
+        assert_eq!(ranges_at(&lines, 1.5), vec![(0., 10.)], "{}", path);
+    }
+}
+
+#[test]
+fn nonzero_leaves_a_hole_with_the_opposite_winding() {
+    let lines = lines(&hatch(
+        &shape(OPPOSITE_WINDING),
+        horizontal(FillRule::NonZero),
+    ));
+    assert_eq!(ranges_at(&lines, 5.5), vec![(0., 3.), (7., 10.)]);
+}
+
+#[test]
+fn cross_hatch_adds_a_crossing_set() {
+    let options = HatchOptions {
+        cross_hatch: true,
+        ..horizontal(FillRule::NonZero)
+    };
+    let lines = lines(&hatch(&shape("M0,0 H10 V10 H0 Z"), options));
+    assert_eq!(lines.len(), 20);
+    let vertical = lines
+        .iter()
+        .filter(|(start, end)| (start.x - end.x).abs() < 1e-9)
+        .count();
+    assert_eq!(vertical, 10);
+}
+
+#[test]
+fn no_spacing_gives_no_lines() {
+    for spacing in [0., -1., f64::NAN] {
+        let options = HatchOptions {
+            spacing,
+            ..HatchOptions::default()
+        };
+        assert!(hatch(&shape("M0,0 H10 V10 H0 Z"), options).is_empty());
+    }
+}
+
+fn offset(fill_rule: FillRule) -> OffsetOptions {
+    OffsetOptions {
+        spacing: 1.,
+        fill_rule,
+    }
+}
+
+fn pen_lifts(line_tos: &[LineTo]) -> usize {
+    line_tos
+        .iter()
+        .filter(|line_to| matches!(line_to, LineTo::Fly(_)))
+        .count()
+}
+
+#[test]
+fn nonzero_rings_ignore_a_sub_path_with_the_same_winding() {
+    // the inner square is inside the fill, so the rings are those of the outer square
+    // alone: at 0.5, 1.5, ... 4.5 in from its sides
+    let options = offset(FillRule::NonZero);
+    let plain = concentric(&shape("M0,0 H10 V10 H0 Z"), options);
+    let nested = concentric(&shape(SAME_WINDING), options);
+    assert_eq!(pen_lifts(&nested), 5);
+    assert_eq!(nested, plain);
+    assert_eq!(pen_lifts(&spiral(&shape(SAME_WINDING), options)), 1);
+}
+
+#[test]
+fn evenodd_rings_go_round_the_hole() {
+    let annulus = shape("M0,0 H10 V10 H0 Z M4,4 H6 V6 H4 Z");
+    let line_tos = concentric(&annulus, offset(FillRule::EvenOdd));
+    // the 4 wide band has rings at 0.5 and 1.5 from either side: two round the outside
+    // and two round the hole
+    assert_eq!(pen_lifts(&line_tos), 4);
+    for line_to in &line_tos {
+        let point = line_to.target();
+        let inside_outer = point.x > 0. && point.x < 10. && point.y > 0. && point.y < 10.;
+        let inside_hole = point.x > 4. && point.x < 6. && point.y > 4. && point.y < 6.;
+        assert!(inside_outer && !inside_hole, "{:?}", point);
+    }
+}
+
+#[test]
+fn finely_flattened_overlapping_circles_ring_their_union() {
+    // two circles, each flattened into 2000 edges, overlapping by half their radius
+    let circles = shape(
+        "M0,0 A20,20 0 0 1 40,0 A20,20 0 0 1 0,0 Z M30,0 A20,20 0 0 1 70,0 A20,20 0 0 1 30,0 Z",
+    );
+    assert!(
+        circles
+            .iter()
+            .map(|sub_path| sub_path.points.len())
+            .sum::<usize>()
+            > 3000
+    );
+    let rings = concentric(&circles, offset(FillRule::NonZero));
+    assert!(pen_lifts(&rings) > 0);
+    // the first ring is half a spacing in from the outline of the union, so it never
+    // crosses where the circles overlap
+    let outer = &rings[..rings[1..]
+        .iter()
+        .position(|line_to| matches!(line_to, LineTo::Fly(_)))
+        .map_or(rings.len(), |index| index + 1)];
+    let centres = [Point::new(20., 0.), Point::new(50., 0.)];
+    let notches = [
+        Point::new(35., 175f64.sqrt()),
+        Point::new(35., -175f64.sqrt()),
+    ];
+    for line_to in outer {
+        let point = line_to.target();
+        // nearest point of the outline: on one circle where it is outside the other,
+        // or at one of the notches where they meet
+        let mut depth = distance(point, notches[0]).min(distance(point, notches[1]));
+        for (index, centre) in centres.iter().enumerate() {
+            let on_circle = *centre + (point - *centre) * (20. / distance(point, *centre));
+            if distance(on_circle, centres[1 - index]) >= 20. {
+                depth = depth.min(distance(point, on_circle));
+            }
+        }
+        assert!((depth - 0.5).abs() < 0.1, "{:?} at {}", point, depth);
+    }
+}
+
+#[test]
+fn non_finite_outlines_give_no_rings() {
+    for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let outline = sub_paths(vec![
+            LineTo::Fly(Point::new(0., 0.)),
+            LineTo::Draw(Point::new(10., 0.)),
+            LineTo::Draw(Point::new(bad, 10.)),
+            LineTo::Draw(Point::new(0., 10.)),
+            LineTo::Draw(Point::new(0., 0.)),
+        ]);
+        assert_eq!(concentric(&outline, offset(FillRule::NonZero)), vec![]);
+        assert_eq!(spiral(&outline, offset(FillRule::EvenOdd)), vec![]);
+    }
+}
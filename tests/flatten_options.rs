@@ -0,0 +1,102 @@
+use svg_to_lines::svg::flatten_options::{FlattenOptions, Flattening};
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::math::distance;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::svg_curve::points_from_path_segments_with_options;
+use svgtypes::PathParser;
+
+fn flatten(path: &str, options: FlattenOptions) -> Vec<LineTo> {
+    let segments = PathParser::from(path).map(|segment| segment.unwrap());
+    points_from_path_segments_with_options(segments, options).collect()
+}
+
+fn draws(line_tos: &[LineTo]) -> usize {
+    line_tos
+        .iter()
+        .filter(|line_to| matches!(line_to, LineTo::Draw(_)))
+        .count()
+}
+
+fn chord_lengths(line_tos: &[LineTo]) -> Vec<f64> {
+    line_tos
+        .windows(2)
+        .map(|ends| distance(ends[0].target(), ends[1].target()))
+        .collect()
+}
+
+#[test]
+fn min_segment_length_drops_points_that_are_too_close() {
+    let path = "M0,0 Q50,80 100,0";
+    let dense = flatten(path, FlattenOptions::default());
+    assert!(chord_lengths(&dense).iter().any(|length| *length < 1.));
+
+    let sparse = flatten(
+        path,
+        FlattenOptions {
+            min_segment_length: 5.,
+            ..Default::default()
+        },
+    );
+    assert!(draws(&sparse) < draws(&dense) / 10, "{}", draws(&sparse));
+    // every chord is long enough but the last, which ends on the end point
+    let lengths = chord_lengths(&sparse);
+    assert!(lengths[..lengths.len() - 1]
+        .iter()
+        .all(|length| *length >= 5.));
+    assert_eq!(sparse.last(), Some(&LineTo::Draw(Point::new(100., 0.))));
+}
+
+#[test]
+fn fixed_step_sets_the_points_per_curve() {
+    let path = "M0,0 Q50,80 100,0";
+    let fixed = |period| {
+        flatten(
+            path,
+            FlattenOptions {
+                flattening: Flattening::FixedStep(period),
+                ..Default::default()
+            },
+        )
+    };
+    // one point per step, whatever the size of the curve
+    assert_eq!(draws(&fixed(0.1)), 10);
+    assert_eq!(draws(&fixed(0.01)), 100);
+    assert_eq!(draws(&fixed(0.001)), 1000);
+    let larger = flatten(
+        "M0,0 Q500,800 1000,0",
+        FlattenOptions {
+            flattening: Flattening::FixedStep(0.01),
+            ..Default::default()
+        },
+    );
+    assert_eq!(draws(&larger), 100);
+    assert_eq!(
+        fixed(0.01).last(),
+        Some(&LineTo::Draw(Point::new(100., 0.)))
+    );
+}
+
+#[test]
+fn straight_curves_are_only_collapsed_when_asked() {
+    let path = "M0,0 C10,0 20,0 30,0";
+    let collapsed = flatten(path, FlattenOptions::default());
+    assert_eq!(
+        collapsed,
+        vec![
+            LineTo::Fly(Point::new(0., 0.)),
+            LineTo::Draw(Point::new(30., 0.)),
+        ]
+    );
+
+    let sampled = flatten(
+        path,
+        FlattenOptions {
+            flattening: Flattening::FixedStep(0.01),
+            collapse_straight_curves: false,
+            ..Default::default()
+        },
+    );
+    assert_eq!(draws(&sampled), 100);
+    assert!(sampled.iter().all(|line_to| line_to.target().y == 0.));
+    assert_eq!(sampled.last(), collapsed.last());
+}
@@ -0,0 +1,59 @@
+use proptest::prelude::*;
+use svg_to_lines::scalar::{Fixed, Scalar};
+use svg_to_lines::svg::forward_differences::ForwardDifferences;
+use svg_to_lines::svg::math::{CubicCurve, CurvePoint, SquareCurve};
+use svg_to_lines::svg::point::Point;
+
+fn point() -> impl Strategy<Value = Point> {
+    (-100.0..100.0f64, -100.0..100.0f64).prop_map(|(x, y)| Point::new(x, y))
+}
+
+// largest distance between the incremental points and direct f64 evaluation
+fn max_error<S: Scalar>(
+    curve: &impl CurvePoint,
+    differences: ForwardDifferences<S>,
+    ticks: usize,
+) -> f64 {
+    let mut count = 0;
+    let error = differences
+        .enumerate()
+        .map(|(index, point)| {
+            count += 1;
+            let expected = curve.at((index + 1) as f64 / ticks as f64);
+            let point = point.cast::<f64>();
+            (point.x - expected.x)
+                .abs()
+                .max((point.y - expected.y).abs())
+        })
+        .fold(0., f64::max);
+    assert_eq!(count, ticks);
+    error
+}
+
+proptest! {
+    #[test]
+    fn cubic_matches_direct_evaluation(
+        start in point(), p1 in point(), p2 in point(), end in point(), ticks in 1..3000usize
+    ) {
+        let curve = CubicCurve::new(start, p1, p2, end);
+        let error = max_error(&curve, ForwardDifferences::<f64>::cubic(start, p1, p2, end, ticks), ticks);
+        prop_assert!(error < 1e-9, "f64 {}", error);
+        let error = max_error(&curve, ForwardDifferences::<f32>::cubic(start, p1, p2, end, ticks), ticks);
+        prop_assert!(error < 2e-3, "f32 {}", error);
+        let error = max_error(&curve, ForwardDifferences::<Fixed>::cubic(start, p1, p2, end, ticks), ticks);
+        prop_assert!(error < 1e-4, "fixed {}", error);
+    }
+
+    #[test]
+    fn quadratic_matches_direct_evaluation(
+        start in point(), p1 in point(), end in point(), ticks in 1..3000usize
+    ) {
+        let curve = SquareCurve::new(start, p1, end);
+        let error = max_error(&curve, ForwardDifferences::<f64>::quadratic(start, p1, end, ticks), ticks);
+        prop_assert!(error < 1e-9, "f64 {}", error);
+        let error = max_error(&curve, ForwardDifferences::<f32>::quadratic(start, p1, end, ticks), ticks);
+        prop_assert!(error < 2e-3, "f32 {}", error);
+        let error = max_error(&curve, ForwardDifferences::<Fixed>::quadratic(start, p1, end, ticks), ticks);
+        prop_assert!(error < 1e-4, "fixed {}", error);
+    }
+}
@@ -0,0 +1,130 @@
+use svg_to_lines::robot::four_cable::{Cable, FourCable};
+use svg_to_lines::robot::kinematics::{Kinematics, Unreachable};
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::point::Point;
+
+fn cable(anchor: (f64, f64), attachment: (f64, f64)) -> Cable {
+    Cable {
+        anchor: Point::new(anchor.0, anchor.1),
+        attachment: Point::new(attachment.0, attachment.1),
+    }
+}
+
+// a 100 x 100 board with the lines leaving from its corners, tied to the pen tip
+fn square_board() -> FourCable {
+    FourCable::new([
+        cable((0., 0.), (0., 0.)),
+        cable((100., 0.), (0., 0.)),
+        cable((100., 100.), (0., 0.)),
+        cable((0., 100.), (0., 0.)),
+    ])
+}
+
+fn assert_lengths(lengths: Option<[f64; 4]>, expected: [f64; 4]) {
+    let lengths = lengths.expect("the pen is reachable");
+    for (length, expected) in lengths.iter().zip(expected.iter()) {
+        assert!(
+            (length - expected).abs() < 1e-9,
+            "{:?} instead of {:?}",
+            lengths,
+            expected
+        );
+    }
+}
+
+#[test]
+fn lengths_run_from_the_anchors_to_the_pen() {
+    let board = square_board();
+    let diagonal = 50. * 2f64.sqrt();
+    assert_lengths(
+        board.cable_lengths(Point::new(50., 50.)),
+        [diagonal, diagonal, diagonal, diagonal],
+    );
+    assert_lengths(
+        board.lengths(Point::new(30., 40.)),
+        [50., 70f64.hypot(40.), 70f64.hypot(60.), 30f64.hypot(60.)],
+    );
+}
+
+#[test]
+fn attachments_move_the_line_ends_and_shrink_the_area() {
+    // a 10 x 10 pen holder with a line tied to each of its corners
+    let board = FourCable::new([
+        cable((0., 0.), (-5., -5.)),
+        cable((100., 0.), (5., -5.)),
+        cable((100., 100.), (5., 5.)),
+        cable((0., 100.), (-5., 5.)),
+    ]);
+    let diagonal = 45. * 2f64.sqrt();
+    assert_lengths(
+        board.cable_lengths(Point::new(50., 50.)),
+        [diagonal, diagonal, diagonal, diagonal],
+    );
+    assert!(board.is_reachable(Point::new(6., 50.)));
+    assert!(!board.is_reachable(Point::new(4., 50.)));
+    assert!(!board.is_reachable(Point::new(50., 96.)));
+}
+
+#[test]
+fn the_pen_stays_inside_the_anchors_and_the_margin() {
+    let mut board = square_board();
+    assert!(board.is_reachable(Point::new(0., 50.)));
+    assert_eq!(board.cable_lengths(Point::new(150., 50.)), None);
+    assert_eq!(board.cable_lengths(Point::new(50., -1.)), None);
+
+    board.margin = 10.;
+    assert!(!board.is_reachable(Point::new(5., 50.)));
+    assert!(board.is_reachable(Point::new(15., 50.)));
+    assert!(!board.is_reachable(Point::new(50., 95.)));
+}
+
+#[test]
+fn anchors_may_be_listed_in_any_order() {
+    // diagonally opposite corners next to each other in the list
+    let board = FourCable::new([
+        cable((0., 0.), (0., 0.)),
+        cable((100., 100.), (0., 0.)),
+        cable((100., 0.), (0., 0.)),
+        cable((0., 100.), (0., 0.)),
+    ]);
+    assert!(board.is_reachable(Point::new(90., 10.)));
+    assert!(board.is_reachable(Point::new(10., 90.)));
+    assert!(!board.is_reachable(Point::new(110., 50.)));
+    assert_lengths(
+        board.cable_lengths(Point::new(30., 40.)),
+        [50., 70f64.hypot(60.), 70f64.hypot(40.), 30f64.hypot(60.)],
+    );
+
+    // anchors in a straight line can not hold the pen anywhere
+    let line = FourCable::new([
+        cable((0., 0.), (0., 0.)),
+        cable((30., 0.), (0., 0.)),
+        cable((60., 0.), (0., 0.)),
+        cable((100., 0.), (0., 0.)),
+    ]);
+    assert!(!line.is_reachable(Point::new(50., 0.)));
+}
+
+#[test]
+fn moves_out_of_reach_are_reported_with_their_index() {
+    let board = square_board();
+    let line_tos = vec![
+        LineTo::Fly(Point::new(50., 50.)),
+        LineTo::Draw(Point::new(120., 50.)),
+        LineTo::Draw(Point::new(50., 100.)),
+    ];
+    let lengths: Vec<_> = board.lengths_for_moves(line_tos.into_iter()).collect();
+    assert_eq!(lengths.len(), 3);
+    assert!(lengths[0].is_ok());
+    assert_eq!(
+        lengths[1],
+        Err(Unreachable {
+            index: 1,
+            point: Point::new(120., 50.),
+        })
+    );
+    assert_lengths(
+        lengths[2].ok(),
+        [50f64.hypot(100.), 50f64.hypot(100.), 50., 50.],
+    );
+}
@@ -0,0 +1,108 @@
+use svg_to_lines::svg::joining::{join_line_tos, join_sub_paths};
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::sub_path::SubPath;
+
+fn sub_path(points: &[(f64, f64)]) -> SubPath {
+    SubPath {
+        points: points.iter().map(|&(x, y)| Point::new(x, y)).collect(),
+    }
+}
+
+#[test]
+fn consecutive_pieces_become_one_stroke() {
+    let joined = join_sub_paths(
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(10., 0.), (10., 10.)]),
+            sub_path(&[(10., 10.), (0., 10.)]),
+        ],
+        0.,
+    );
+    assert_eq!(
+        joined,
+        vec![sub_path(&[(0., 0.), (10., 0.), (10., 10.), (0., 10.)])]
+    );
+}
+
+#[test]
+fn pieces_are_reversed_to_fit() {
+    // the second piece ends where the first ends
+    let joined = join_sub_paths(
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(10., 10.), (10., 0.)]),
+        ],
+        0.,
+    );
+    assert_eq!(joined, vec![sub_path(&[(0., 0.), (10., 0.), (10., 10.)])]);
+
+    // the second piece starts where the first starts, so it goes in front, reversed
+    let joined = join_sub_paths(
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(0., 0.), (0., 10.)]),
+        ],
+        0.,
+    );
+    assert_eq!(joined, vec![sub_path(&[(0., 10.), (0., 0.), (10., 0.)])]);
+}
+
+#[test]
+fn pieces_anywhere_in_the_input_are_joined() {
+    let joined = join_sub_paths(
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(50., 50.), (60., 50.)]),
+            sub_path(&[(20., 0.), (10., 0.)]),
+        ],
+        0.,
+    );
+    assert_eq!(
+        joined,
+        vec![
+            sub_path(&[(0., 0.), (10., 0.), (20., 0.)]),
+            sub_path(&[(50., 50.), (60., 50.)]),
+        ]
+    );
+}
+
+#[test]
+fn gaps_within_the_tolerance_are_bridged() {
+    let pieces = vec![
+        sub_path(&[(0., 0.), (10., 0.)]),
+        sub_path(&[(10.05, 0.), (20., 0.)]),
+    ];
+    assert_eq!(join_sub_paths(pieces.clone(), 0.01), pieces);
+    assert_eq!(
+        join_sub_paths(pieces, 0.1),
+        vec![sub_path(&[(0., 0.), (10., 0.), (10.05, 0.), (20., 0.)])]
+    );
+}
+
+#[test]
+fn loops_are_left_alone() {
+    let pieces = vec![
+        sub_path(&[(0., 0.), (10., 0.), (10., 10.), (0., 0.)]),
+        sub_path(&[(0., 0.), (-10., 0.)]),
+    ];
+    assert_eq!(join_sub_paths(pieces.clone(), 0.), pieces);
+}
+
+#[test]
+fn joined_line_tos_lift_the_pen_once() {
+    let line_tos = vec![
+        LineTo::Fly(Point::new(0., 0.)),
+        LineTo::Draw(Point::new(10., 0.)),
+        LineTo::Fly(Point::new(20., 0.)),
+        LineTo::Draw(Point::new(10., 0.)),
+    ];
+    assert_eq!(
+        join_line_tos(line_tos, 0.),
+        vec![
+            LineTo::Fly(Point::new(0., 0.)),
+            LineTo::Draw(Point::new(10., 0.)),
+            LineTo::Draw(Point::new(20., 0.)),
+        ]
+    );
+}
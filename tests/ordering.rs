@@ -0,0 +1,158 @@
+use proptest::prelude::*;
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::ordering::{order_line_tos, order_sub_paths, pen_up_distance};
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::sub_path::SubPath;
+
+fn sub_path(points: &[(f64, f64)]) -> SubPath {
+    SubPath {
+        points: points.iter().map(|&(x, y)| Point::new(x, y)).collect(),
+    }
+}
+
+// the drawn segments, each with its ends sorted, so that direction and loop start
+// do not matter
+fn strokes(sub_paths: &[SubPath]) -> Vec<[u64; 4]> {
+    let mut strokes: Vec<[u64; 4]> = sub_paths
+        .iter()
+        .flat_map(|sub_path| sub_path.points.windows(2))
+        .map(|ends| {
+            let (a, b) = (ends[0], ends[1]);
+            let (a, b) = if (a.x, a.y) <= (b.x, b.y) {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            [a.x.to_bits(), a.y.to_bits(), b.x.to_bits(), b.y.to_bits()]
+        })
+        .collect();
+    strokes.sort_unstable();
+    strokes
+}
+
+#[test]
+fn open_sub_paths_are_reordered_and_reversed() {
+    let input = vec![
+        sub_path(&[(20., 0.), (30., 0.)]),
+        sub_path(&[(10., 0.), (0., 0.)]),
+    ];
+    assert_eq!(pen_up_distance(&input, Point::ZERO), 40.);
+    let (ordered, report) = order_sub_paths(input.clone(), Point::ZERO);
+    assert_eq!(report.pen_up_before, 40.);
+    assert_eq!(report.pen_up_after, 10.);
+    assert_eq!(
+        ordered,
+        vec![
+            sub_path(&[(0., 0.), (10., 0.)]),
+            sub_path(&[(20., 0.), (30., 0.)]),
+        ]
+    );
+    assert_eq!(strokes(&ordered), strokes(&input));
+}
+
+#[test]
+fn loops_start_at_the_nearest_point() {
+    let square = sub_path(&[(10., 0.), (20., 0.), (20., 10.), (10., 10.), (10., 0.)]);
+    let start = Point::new(21., 11.);
+    let (ordered, report) = order_sub_paths(vec![square.clone()], start);
+    assert_eq!(
+        ordered,
+        vec![sub_path(&[
+            (20., 10.),
+            (10., 10.),
+            (10., 0.),
+            (20., 0.),
+            (20., 10.)
+        ])]
+    );
+    assert!((report.pen_up_after - 2f64.sqrt()).abs() < 1e-12);
+    assert_eq!(strokes(&ordered), strokes(&[square]));
+}
+
+#[test]
+fn a_good_order_is_kept() {
+    let input = vec![
+        sub_path(&[(0., 0.), (10., 0.)]),
+        sub_path(&[(10., 5.), (0., 5.)]),
+        sub_path(&[(0., 10.), (10., 10.)]),
+    ];
+    let (ordered, report) = order_sub_paths(input.clone(), Point::ZERO);
+    assert_eq!(ordered, input);
+    assert_eq!(report.pen_up_before, 10.);
+    assert_eq!(report.pen_up_after, 10.);
+}
+
+#[test]
+fn many_sub_paths_are_ordered() {
+    // dashes on a 100 by 100 grid, in a scrambled order and direction
+    let mut input = Vec::new();
+    for index in 0..10_000_u64 {
+        let scrambled = index * 7919 % 10_000;
+        let (x, y) = (
+            (scrambled % 100) as f64 * 10.,
+            (scrambled / 100) as f64 * 10.,
+        );
+        input.push(if index % 2 == 0 {
+            sub_path(&[(x, y), (x + 5., y)])
+        } else {
+            sub_path(&[(x + 5., y), (x, y)])
+        });
+    }
+    let (ordered, report) = order_sub_paths(input.clone(), Point::ZERO);
+    assert!(report.pen_up_after < report.pen_up_before / 10.);
+    assert_eq!(strokes(&ordered), strokes(&input));
+}
+
+#[test]
+fn line_tos_come_back_as_fly_and_draw() {
+    let line_tos = vec![
+        LineTo::Fly(Point::new(20., 0.)),
+        LineTo::Draw(Point::new(30., 0.)),
+        LineTo::Fly(Point::new(10., 0.)),
+        LineTo::Draw(Point::new(0., 0.)),
+    ];
+    let (ordered, report) = order_line_tos(line_tos, Point::ZERO);
+    assert_eq!(
+        ordered,
+        vec![
+            LineTo::Fly(Point::new(0., 0.)),
+            LineTo::Draw(Point::new(10., 0.)),
+            LineTo::Fly(Point::new(20., 0.)),
+            LineTo::Draw(Point::new(30., 0.)),
+        ]
+    );
+    assert_eq!(report.pen_up_after, 10.);
+}
+
+fn coord() -> impl Strategy<Value = f64> {
+    (-100i32..100).prop_map(f64::from)
+}
+
+fn point() -> impl Strategy<Value = (f64, f64)> {
+    (coord(), coord())
+}
+
+fn any_sub_path() -> impl Strategy<Value = SubPath> {
+    prop_oneof![
+        prop::collection::vec(point(), 2..5).prop_map(|points| sub_path(&points)),
+        prop::collection::vec(point(), 3..5).prop_map(|mut points| {
+            points.push(points[0]);
+            sub_path(&points)
+        }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn ordering_draws_the_same_strokes_with_less_pen_up_travel(
+        input in prop::collection::vec(any_sub_path(), 0..12),
+        start in point(),
+    ) {
+        let start = Point::new(start.0, start.1);
+        let (ordered, report) = order_sub_paths(input.clone(), start);
+        prop_assert_eq!(report.pen_up_before, pen_up_distance(&input, start));
+        prop_assert!((report.pen_up_after - pen_up_distance(&ordered, start)).abs() < 1e-9);
+        prop_assert!(report.pen_up_after <= report.pen_up_before);
+        prop_assert_eq!(strokes(&ordered), strokes(&input));
+    }
+}
@@ -0,0 +1,148 @@
+use svg_to_lines::svg::flatten_options::FlattenOptions;
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::path_error::{PathError, PathErrorKind};
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::svg_curve::{
+    try_points_from_path_segments, try_points_from_path_segments_with_options,
+};
+use svg_to_lines::svg::transform::Transform;
+use svgtypes::{PathParser, PathSegment};
+
+// the moves before the error, and the error
+fn flatten(segments: Vec<PathSegment>) -> (Vec<LineTo>, PathError) {
+    split(try_points_from_path_segments(segments.into_iter().map(Ok)).collect())
+}
+
+fn split(results: Vec<Result<LineTo, PathError>>) -> (Vec<LineTo>, PathError) {
+    let mut line_tos = Vec::new();
+    let mut results = results.into_iter();
+    for result in &mut results {
+        match result {
+            Ok(line_to) => line_tos.push(line_to),
+            Err(error) => {
+                assert!(results.next().is_none(), "moves after the error");
+                return (line_tos, error);
+            }
+        }
+    }
+    panic!("no error in {:?}", line_tos)
+}
+
+fn move_to(x: f64, y: f64) -> PathSegment {
+    PathSegment::MoveTo { abs: true, x, y }
+}
+
+fn line_to(x: f64, y: f64) -> PathSegment {
+    PathSegment::LineTo { abs: true, x, y }
+}
+
+fn arc_to(rx: f64, ry: f64) -> PathSegment {
+    PathSegment::EllipticalArc {
+        abs: true,
+        rx,
+        ry,
+        x_axis_rotation: 0.,
+        large_arc: false,
+        sweep: true,
+        x: 10.,
+        y: 0.,
+    }
+}
+
+#[test]
+fn parse_errors_name_the_segment_and_byte() {
+    let path = "M10,10 L20,20 L3x0,5";
+    let (line_tos, error) = split(try_points_from_path_segments(PathParser::from(path)).collect());
+    assert_eq!(
+        line_tos,
+        vec![
+            LineTo::Fly(Point::new(10., 10.)),
+            LineTo::Draw(Point::new(20., 20.)),
+        ]
+    );
+    assert_eq!(error.segment, 2);
+    match error.kind {
+        PathErrorKind::Parse { position, .. } => {
+            assert_eq!(position, Some(path.find('x').unwrap()))
+        }
+        kind => panic!("expected a parse error, got {:?}", kind),
+    }
+    let message = error.to_string();
+    assert!(message.starts_with("segment 2: "), "{}", message);
+    assert!(message.contains("(byte 16)"), "{}", message);
+}
+
+#[test]
+fn parse_errors_at_the_start() {
+    let (line_tos, error) = split(try_points_from_path_segments(PathParser::from("X")).collect());
+    assert!(line_tos.is_empty());
+    assert_eq!(error.segment, 0);
+    assert!(matches!(
+        error.kind,
+        PathErrorKind::Parse {
+            position: Some(0),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn non_finite_coordinates_stop_the_stream() {
+    for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let (line_tos, error) = flatten(vec![move_to(0., 0.), line_to(5., 5.), line_to(bad, 1.)]);
+        assert_eq!(line_tos.last(), Some(&LineTo::Draw(Point::new(5., 5.))));
+        assert_eq!(error.segment, 2);
+        assert!(matches!(error.kind, PathErrorKind::NonFiniteCoordinate));
+    }
+}
+
+#[test]
+fn non_finite_radii_are_told_apart() {
+    for (rx, ry) in [
+        (f64::NAN, 5.),
+        (5., f64::NAN),
+        (f64::INFINITY, 5.),
+        (5., f64::NEG_INFINITY),
+    ] {
+        let (_, error) = flatten(vec![move_to(0., 0.), arc_to(rx, ry)]);
+        assert_eq!(error.segment, 1);
+        assert!(
+            matches!(error.kind, PathErrorKind::NonFiniteRadius),
+            "{} {}: {:?}",
+            rx,
+            ry,
+            error.kind
+        );
+    }
+    assert_eq!(
+        flatten(vec![move_to(0., 0.), arc_to(f64::NAN, 5.)])
+            .1
+            .to_string(),
+        "segment 1: arc radius is not finite"
+    );
+}
+
+#[test]
+fn overflow_while_resolving_is_caught() {
+    // every number is finite, but the relative moves add up to infinity
+    let path = "M0,0 m1e308,0 m1e308,0 l1,1";
+    let (line_tos, error) = split(try_points_from_path_segments(PathParser::from(path)).collect());
+    assert_eq!(line_tos.len(), 2);
+    assert_eq!(error.segment, 2);
+    assert!(matches!(error.kind, PathErrorKind::NonFiniteCoordinate));
+}
+
+#[test]
+fn overflow_through_the_transform_is_caught() {
+    let options = FlattenOptions {
+        transform: Transform::scale(1e300, 1e300),
+        ..Default::default()
+    };
+    let path = "M1,1 L1e10,0";
+    let (line_tos, error) = split(
+        try_points_from_path_segments_with_options(PathParser::from(path), options).collect(),
+    );
+    assert_eq!(line_tos, vec![LineTo::Fly(Point::new(1e300, 1e300))]);
+    assert_eq!(error.segment, 1);
+    assert!(matches!(error.kind, PathErrorKind::NonFiniteCoordinate));
+}
@@ -0,0 +1,268 @@
+use std::str::FromStr;
+
+use svg_to_lines::svg::document::placed_points_from_document;
+use svg_to_lines::svg::flatten_options::FlattenOptions;
+use svg_to_lines::svg::line_to::{CircularArc, LineTo};
+use svg_to_lines::svg::math::distance;
+use svg_to_lines::svg::placement::*;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::transform::Transform;
+use svgtypes::{AspectRatio, Length, LengthUnit, ViewBox};
+
+fn assert_near(point: Point, expected: Point) {
+    assert!(
+        distance(point, expected) < 1e-9,
+        "{:?} instead of {:?}",
+        point,
+        expected
+    );
+}
+
+// where the corners of a 100 x 50 viewBox end up in a 200 x 200 viewport
+fn corners(aspect: &str) -> (Point, Point) {
+    let transform = view_box_transform(
+        ViewBox::new(0., 0., 100., 50.),
+        AspectRatio::from_str(aspect).unwrap(),
+        Rect::new(0., 0., 200., 200.),
+    );
+    (
+        transform.apply(Point::new(0., 0.)),
+        transform.apply(Point::new(100., 50.)),
+    )
+}
+
+#[test]
+fn view_box_meet_fits_and_aligns() {
+    let (min, max) = corners("xMidYMid meet");
+    assert_near(min, Point::new(0., 50.));
+    assert_near(max, Point::new(200., 150.));
+    let (min, max) = corners("xMinYMax");
+    assert_near(min, Point::new(0., 100.));
+    assert_near(max, Point::new(200., 200.));
+}
+
+#[test]
+fn view_box_slice_fills_and_overflows() {
+    let (min, max) = corners("xMidYMid slice");
+    assert_near(min, Point::new(-100., 0.));
+    assert_near(max, Point::new(300., 200.));
+    let (min, max) = corners("xMinYMin slice");
+    assert_near(min, Point::new(0., 0.));
+    assert_near(max, Point::new(400., 200.));
+}
+
+#[test]
+fn view_box_none_stretches() {
+    let (min, max) = corners("none");
+    assert_near(min, Point::new(0., 0.));
+    assert_near(max, Point::new(200., 200.));
+}
+
+#[test]
+fn lengths_convert_to_px_and_mm() {
+    let px = |num, unit| length_in_px(Length::new(num, unit)).unwrap();
+    assert_eq!(px(3., LengthUnit::None), 3.);
+    assert_eq!(px(3., LengthUnit::Px), 3.);
+    assert_eq!(px(1., LengthUnit::In), 96.);
+    assert!((px(2.54, LengthUnit::Cm) - 96.).abs() < 1e-9);
+    assert!((px(25.4, LengthUnit::Mm) - 96.).abs() < 1e-9);
+    assert!((px(72., LengthUnit::Pt) - 96.).abs() < 1e-9);
+    assert_eq!(px(6., LengthUnit::Pc), 96.);
+    assert_eq!(length_in_px(Length::new(50., LengthUnit::Percent)), None);
+
+    let mm = length_in_mm(Length::new(2., LengthUnit::In)).unwrap();
+    assert!((mm - 50.8).abs() < 1e-9);
+    assert!((length_in_mm(Length::new(96., LengthUnit::None)).unwrap() - 25.4).abs() < 1e-9);
+}
+
+#[test]
+fn placement_keeps_margins_and_aligns() {
+    let drawing = Rect::new(5., 5., 40., 20.);
+    let mut placement = Placement {
+        margin: 10.,
+        ..Placement::new(Rect::new(0., 0., 100., 100.))
+    };
+    assert_eq!(placement.area(), Rect::new(10., 10., 80., 80.));
+
+    // twice the size fills the width; the height is centred
+    let transform = placement.transform(drawing);
+    assert_near(transform.apply(Point::new(5., 5.)), Point::new(10., 30.));
+    assert_near(transform.apply(Point::new(45., 25.)), Point::new(90., 70.));
+
+    placement.align_y = Align::Start;
+    assert_near(
+        placement.transform(drawing).apply(Point::new(5., 5.)),
+        Point::new(10., 10.),
+    );
+    placement.align_y = Align::End;
+    assert_near(
+        placement.transform(drawing).apply(Point::new(45., 25.)),
+        Point::new(90., 90.),
+    );
+
+    placement.fit = Fit::ShrinkToFit;
+    placement.align_x = Align::Start;
+    let transform = placement.transform(drawing);
+    assert_near(transform.apply(Point::new(5., 5.)), Point::new(10., 70.));
+    assert_near(transform.apply(Point::new(45., 25.)), Point::new(50., 90.));
+}
+
+#[test]
+fn placement_cuts_what_does_not_fit() {
+    let placement = Placement {
+        margin: 10.,
+        fit: Fit::ActualSize,
+        align_x: Align::Start,
+        align_y: Align::Start,
+        ..Placement::new(Rect::new(0., 0., 100., 100.))
+    };
+    let line_tos = vec![
+        LineTo::Fly(Point::new(0., 50.)),
+        LineTo::Draw(Point::new(200., 50.)),
+        LineTo::Fly(Point::new(150., 0.)),
+        LineTo::Draw(Point::new(50., 20.)),
+    ];
+    let placed: Vec<LineTo> = placement
+        .apply(Rect::new(0., 0., 200., 100.), line_tos.into_iter())
+        .collect();
+    assert_eq!(
+        placed,
+        vec![
+            LineTo::Fly(Point::new(10., 60.)),
+            LineTo::Draw(Point::new(90., 60.)),
+            LineTo::Fly(Point::new(90., 24.)),
+            LineTo::Draw(Point::new(60., 30.)),
+        ]
+    );
+}
+
+#[test]
+fn clipping_keeps_arcs_that_fit_and_cuts_the_others() {
+    let rect = Rect::new(0., 0., 100., 100.);
+    let inside = LineTo::Arc(CircularArc {
+        end: Point::new(60., 50.),
+        center: Point::new(50., 50.),
+        radius: 10.,
+        clockwise: true,
+    });
+    let clipped: Vec<LineTo> = clip_line_tos(
+        vec![LineTo::Fly(Point::new(40., 50.)), inside].into_iter(),
+        rect,
+    )
+    .collect();
+    assert_eq!(clipped, vec![LineTo::Fly(Point::new(40., 50.)), inside]);
+
+    // a half circle bulging out of the top
+    let bulging = LineTo::Arc(CircularArc {
+        end: Point::new(70., 10.),
+        center: Point::new(50., 10.),
+        radius: 20.,
+        clockwise: true,
+    });
+    let clipped: Vec<LineTo> = clip_line_tos(
+        vec![LineTo::Fly(Point::new(30., 10.)), bulging].into_iter(),
+        rect,
+    )
+    .collect();
+    assert!(clipped
+        .iter()
+        .all(|line_to| rect.contains(line_to.target())));
+    assert_eq!(clipped.last(), Some(&LineTo::Draw(Point::new(70., 10.))));
+    assert_eq!(
+        clipped
+            .iter()
+            .filter(|line_to| matches!(line_to, LineTo::Fly(_)))
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn sliced_documents_are_cut_to_the_page() {
+    let text = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100mm" height="50mm"
+                       viewBox="0 0 100 100" preserveAspectRatio="xMidYMid slice">
+                    <path stroke="black" d="M50,0 V100 M0,50 H100"/>
+                  </svg>"#;
+    let placement = Placement::new(Rect::new(0., 0., 100., 50.));
+    let points = placed_points_from_document(text, &placement, FlattenOptions::default()).unwrap();
+    let expected = [
+        LineTo::Fly(Point::new(50., 0.)),
+        LineTo::Draw(Point::new(50., 50.)),
+        LineTo::Fly(Point::new(0., 25.)),
+        LineTo::Draw(Point::new(100., 25.)),
+    ];
+    assert_eq!(points.len(), expected.len(), "{:?}", points);
+    for (line_to, expected) in points.iter().zip(&expected) {
+        assert_eq!(
+            core::mem::discriminant(line_to),
+            core::mem::discriminant(expected)
+        );
+        assert_near(line_to.target(), expected.target());
+    }
+}
+
+#[test]
+fn page_size_comes_from_units() {
+    let text = r#"<svg xmlns="http://www.w3.org/2000/svg" width="2in" height="1in"
+                       viewBox="0 0 20 10"><path stroke="black" d="M0,0 L20,10"/></svg>"#;
+    let placement = Placement {
+        fit: Fit::ActualSize,
+        align_x: Align::Start,
+        align_y: Align::Start,
+        ..Placement::new(Rect::new(0., 0., 1000., 1000.))
+    };
+    let points = placed_points_from_document(text, &placement, FlattenOptions::default()).unwrap();
+    assert_near(points.last().unwrap().target(), Point::new(50.8, 25.4));
+}
+
+#[test]
+fn placed_documents_are_transformed_before_flattening_and_clipping() {
+    let document = |body: &str| {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100mm" height="100mm"
+                    viewBox="0 0 100 100">{}</svg>"#,
+            body
+        )
+    };
+    let circle = document(r#"<circle stroke="black" fill="none" cx="50" cy="50" r="40"/>"#);
+    let area = Rect::new(0., 0., 150., 100.);
+    let placement = Placement {
+        fit: Fit::ActualSize,
+        align_x: Align::Start,
+        align_y: Align::Start,
+        ..Placement::new(area)
+    };
+    let place = |text: &str, transform: Transform| {
+        let options = FlattenOptions {
+            transform,
+            ..FlattenOptions::with_tolerance(0.01)
+        };
+        placed_points_from_document(text, &placement, options).unwrap()
+    };
+
+    // stretched sideways the circle is an ellipse, and the tolerance holds on the
+    // board, after the stretch, so that takes more points
+    let stretched = place(&circle, Transform::scale(1.5, 1.));
+    for line_to in &stretched {
+        let point = line_to.target();
+        let x = (point.x - 75.) / 60.;
+        let y = (point.y - 50.) / 40.;
+        assert!((x * x + y * y - 1.).abs() < 1e-9, "{:?}", point);
+    }
+    assert!(stretched.len() > place(&circle, Transform::IDENTITY).len());
+
+    // moved partly off the area, it is cut off at its edge
+    let moved = place(&circle, Transform::translate(80., 0.));
+    assert!(moved.iter().all(|line_to| area.contains(line_to.target())));
+    assert!(moved
+        .iter()
+        .any(|line_to| (line_to.target().x - 150.).abs() < 1e-9));
+
+    // skewed, the page is a parallelogram that still cuts off what is outside it: of
+    // the line from x = -50 to 150 on the page only 0 to 100 is left, moved by y
+    let line = document(r#"<path stroke="black" d="M-50,20 H150"/>"#);
+    let skewed = place(&line, Transform::skew_x(45.));
+    assert_eq!(skewed.len(), 2, "{:?}", skewed);
+    assert_near(skewed[0].target(), Point::new(20., 20.));
+    assert_near(skewed[1].target(), Point::new(120., 20.));
+}
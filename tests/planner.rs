@@ -0,0 +1,258 @@
+use svg_to_lines::robot::kinematics::Unreachable;
+use svg_to_lines::robot::planner::{plan_line_tos, Limits, PlannerOptions, TimedMove};
+use svg_to_lines::robot::v_plotter::VPlotter;
+use svg_to_lines::svg::line_to::{CircularArc, LineTo};
+use svg_to_lines::svg::point::Point;
+
+fn plotter() -> VPlotter {
+    VPlotter::new(Point::new(0., 0.), Point::new(1000., 0.), 0.)
+}
+
+// pen limits only, the lines may go as fast as they like
+fn pen_limits(velocity: f64, acceleration: f64, corner_jump: f64) -> Limits<2> {
+    Limits {
+        velocity,
+        acceleration,
+        line_velocity: [f64::INFINITY; 2],
+        line_acceleration: [f64::INFINITY; 2],
+        corner_jump,
+    }
+}
+
+fn options(limits: Limits<2>) -> PlannerOptions<2> {
+    PlannerOptions {
+        draw: limits,
+        fly: limits,
+    }
+}
+
+fn assert_near(value: f64, expected: f64) {
+    assert!(
+        (value - expected).abs() < 1e-9,
+        "{} instead of {}",
+        value,
+        expected
+    );
+}
+
+// the distance covered speeding up, cruising and slowing down is the whole move
+fn assert_consistent(timed: &TimedMove) {
+    let accelerate = (timed.entry + timed.cruise) / 2. * timed.accelerate_time;
+    let cruise = timed.cruise * timed.cruise_time;
+    let decelerate = (timed.cruise + timed.exit) / 2. * timed.decelerate_time;
+    assert_near(accelerate + cruise + decelerate, timed.length);
+    assert!(timed.entry <= timed.cruise + 1e-9 && timed.exit <= timed.cruise + 1e-9);
+}
+
+// every test starts with the pen at rest where the first move goes
+fn plan(line_tos: Vec<LineTo>, options: &PlannerOptions<2>) -> Vec<TimedMove> {
+    let start = line_tos[0].target();
+    let moves = plan_line_tos(&plotter(), line_tos, start, options).unwrap();
+    moves.iter().for_each(assert_consistent);
+    moves
+}
+
+#[test]
+fn long_moves_cruise_and_short_ones_do_not() {
+    let options = options(pen_limits(100., 1000., 0.));
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(100., 500.)),
+            LineTo::Draw(Point::new(400., 500.)),
+        ],
+        &options,
+    );
+    assert_eq!(moves[0].length, 0.);
+    let draw = moves[1];
+    assert_eq!((draw.entry, draw.cruise, draw.exit), (0., 100., 0.));
+    assert_near(draw.accelerate_time, 0.1);
+    assert_near(draw.decelerate_time, 0.1);
+    assert_near(draw.cruise_time, 290. / 100.);
+    assert_near(draw.duration(), 3.1);
+
+    // 4 units are not enough to reach full speed: a triangle peaking halfway
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(100., 500.)),
+            LineTo::Draw(Point::new(104., 500.)),
+        ],
+        &options,
+    );
+    let draw = moves[1];
+    assert_near(draw.cruise, (1000f64 * 4.).sqrt());
+    assert_eq!(draw.cruise_time, 0.);
+}
+
+#[test]
+fn speed_carries_through_joins_but_not_pen_changes() {
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(100., 500.)),
+            LineTo::Draw(Point::new(200., 500.)),
+            LineTo::Draw(Point::new(300., 500.)),
+            LineTo::Draw(Point::new(300., 600.)),
+            LineTo::Fly(Point::new(500., 600.)),
+        ],
+        &options(pen_limits(100., 1000., 20.)),
+    );
+    // lowering and lifting the pen
+    assert_eq!(moves[1].entry, 0.);
+    assert_eq!(moves[3].exit, 0.);
+    assert_eq!(moves[4].entry, 0.);
+    assert_eq!(moves[4].exit, 0.);
+
+    // a straight join is taken faster than a right angle, which changes the pen
+    // velocity by its speed times the square root of two
+    assert!(moves[1].exit > 0.);
+    assert_eq!(moves[1].exit, moves[2].entry);
+    assert_eq!(moves[2].exit, moves[3].entry);
+    assert!(moves[2].exit > 0.);
+    assert!(moves[2].exit <= 20. / 2f64.sqrt());
+    assert!(moves[1].exit > moves[2].exit);
+
+    // with no jump allowed every corner is taken from rest
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(100., 500.)),
+            LineTo::Draw(Point::new(300., 500.)),
+            LineTo::Draw(Point::new(300., 700.)),
+        ],
+        &options(pen_limits(100., 1000., 0.)),
+    );
+    assert_eq!(moves[1].exit, 0.);
+    assert_eq!(moves[2].entry, 0.);
+}
+
+#[test]
+fn draw_and_fly_moves_use_their_own_limits() {
+    let options = PlannerOptions {
+        draw: pen_limits(50., 1000., 0.),
+        fly: pen_limits(200., 1000., 0.),
+    };
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(100., 500.)),
+            LineTo::Draw(Point::new(400., 500.)),
+            LineTo::Fly(Point::new(800., 500.)),
+        ],
+        &options,
+    );
+    assert_eq!(moves[1].cruise, 50.);
+    assert_eq!(moves[2].cruise, 200.);
+}
+
+#[test]
+fn line_limits_slow_the_pen() {
+    // straight down from the middle both lines change by about 0.71 per unit of travel
+    let limits = Limits {
+        line_velocity: [10., 10.],
+        ..pen_limits(100., 1000., 0.)
+    };
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(500., 499.)),
+            LineTo::Draw(Point::new(500., 501.)),
+        ],
+        &options(limits),
+    );
+    let rate = (500f64.hypot(501.) - 500f64.hypot(499.)) / 2.;
+    assert_near(moves[1].cruise, 10. / rate);
+    assert!(moves[1].cruise < 15.);
+}
+
+#[test]
+fn the_first_move_starts_from_the_given_pen() {
+    let moves = plan_line_tos(
+        &plotter(),
+        vec![LineTo::Fly(Point::new(400., 500.))],
+        Point::new(100., 500.),
+        &options(pen_limits(100., 1000., 0.)),
+    )
+    .unwrap();
+    moves.iter().for_each(assert_consistent);
+    assert_eq!(moves[0].length, 300.);
+    assert_near(moves[0].duration(), 3.1);
+}
+
+#[test]
+fn arcs_are_timed_along_the_curve() {
+    // half a circle of radius 100, anticlockwise on screen through the bottom
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(400., 500.)),
+            LineTo::Arc(CircularArc {
+                end: Point::new(600., 500.),
+                center: Point::new(500., 500.),
+                radius: 100.,
+                clockwise: false,
+            }),
+        ],
+        &options(pen_limits(100., 1000., 0.)),
+    );
+    let arc = moves[1];
+    assert_near(arc.length, 100. * std::f64::consts::PI);
+    assert_eq!(arc.cruise, 100.);
+    assert_near(arc.duration(), 0.2 + (arc.length - 10.) / 100.);
+
+    // both ends are as far from each motor, so the chord would not change the lines,
+    // but along the arc they do: going sideways through the bottom at (500, 600) each
+    // line changes by 500 / 781 per unit of travel
+    let limits = Limits {
+        line_velocity: [10., 10.],
+        ..pen_limits(100., 1000., 0.)
+    };
+    let moves = plan(
+        vec![
+            LineTo::Fly(Point::new(400., 500.)),
+            LineTo::Arc(CircularArc {
+                end: Point::new(600., 500.),
+                center: Point::new(500., 500.),
+                radius: 100.,
+                clockwise: false,
+            }),
+        ],
+        &options(limits),
+    );
+    let bottom_rate = 500. / 500f64.hypot(600.);
+    assert!(
+        moves[1].cruise <= 10. / bottom_rate + 1e-9,
+        "{}",
+        moves[1].cruise
+    );
+    assert!(moves[1].cruise > 5.);
+}
+
+#[test]
+fn unreachable_moves_are_reported() {
+    let result = plan_line_tos(
+        &plotter(),
+        vec![
+            LineTo::Fly(Point::new(500., 500.)),
+            LineTo::Draw(Point::new(500., -5.)),
+        ],
+        Point::new(500., 500.),
+        &options(pen_limits(100., 1000., 0.)),
+    );
+    assert_eq!(
+        result,
+        Err(Unreachable {
+            index: 1,
+            point: Point::new(500., -5.),
+        })
+    );
+
+    // so is a start out of reach, against the first move
+    let result = plan_line_tos(
+        &plotter(),
+        vec![LineTo::Fly(Point::new(500., 500.))],
+        Point::new(500., -5.),
+        &options(pen_limits(100., 1000., 0.)),
+    );
+    assert_eq!(
+        result,
+        Err(Unreachable {
+            index: 0,
+            point: Point::new(500., -5.),
+        })
+    );
+}
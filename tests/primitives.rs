@@ -0,0 +1,201 @@
+use svg_to_lines::svg::biarc::{arcs_from_path_segments, arcs_from_primitives};
+use svg_to_lines::svg::flatten_options::FlattenOptions;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::primitive::{primitives_from_path_segments, Primitive};
+use svg_to_lines::svg::svg_curve::{
+    points_from_path_segments_with_options, points_from_primitives,
+};
+use svgtypes::PathParser;
+
+fn primitives(path: &str) -> Vec<Primitive> {
+    primitives_from_path_segments(PathParser::from(path).map(|segment| segment.unwrap())).collect()
+}
+
+fn point(x: f64, y: f64) -> Point {
+    Point::new(x, y)
+}
+
+#[test]
+fn relative_lines_are_made_absolute() {
+    assert_eq!(
+        primitives("M5,5 h10 v-3 l-5,-2 H0 V1"),
+        vec![
+            Primitive::Move { end: point(5., 5.) },
+            Primitive::Line {
+                start: point(5., 5.),
+                end: point(15., 5.)
+            },
+            Primitive::Line {
+                start: point(15., 5.),
+                end: point(15., 2.)
+            },
+            Primitive::Line {
+                start: point(15., 2.),
+                end: point(10., 0.)
+            },
+            Primitive::Line {
+                start: point(10., 0.),
+                end: point(0., 0.)
+            },
+            Primitive::Line {
+                start: point(0., 0.),
+                end: point(0., 1.)
+            },
+        ]
+    );
+}
+
+#[test]
+fn relative_move_after_close_starts_from_the_sub_path_start() {
+    assert_eq!(
+        primitives("M10,10 L20,10 L20,20 Z m5,5")[3..],
+        [
+            Primitive::Line {
+                start: point(20., 20.),
+                end: point(10., 10.)
+            },
+            Primitive::Move {
+                end: point(15., 15.)
+            },
+        ]
+    );
+}
+
+#[test]
+fn smooth_cubic_mirrors_the_previous_control_point() {
+    let expected = vec![
+        Primitive::Move {
+            end: point(10., 10.),
+        },
+        Primitive::Cubic {
+            start: point(10., 10.),
+            p1: point(20., 10.),
+            p2: point(30., 20.),
+            end: point(30., 30.),
+        },
+        Primitive::Cubic {
+            start: point(30., 30.),
+            p1: point(30., 40.),
+            p2: point(40., 50.),
+            end: point(50., 50.),
+        },
+    ];
+    assert_eq!(
+        primitives("M10,10 C20,10 30,20 30,30 S40,50 50,50"),
+        expected
+    );
+    assert_eq!(
+        primitives("M10,10 c10,0 20,10 20,20 s10,20 20,20"),
+        expected
+    );
+}
+
+#[test]
+fn smooth_quadratic_mirrors_the_previous_control_point() {
+    let expected = vec![
+        Primitive::Move { end: point(0., 0.) },
+        Primitive::Quadratic {
+            start: point(0., 0.),
+            p1: point(10., 10.),
+            end: point(20., 0.),
+        },
+        Primitive::Quadratic {
+            start: point(20., 0.),
+            p1: point(30., -10.),
+            end: point(40., 0.),
+        },
+        Primitive::Quadratic {
+            start: point(40., 0.),
+            p1: point(50., 10.),
+            end: point(60., 0.),
+        },
+    ];
+    assert_eq!(primitives("M0,0 Q10,10 20,0 T40,0 T60,0"), expected);
+    assert_eq!(primitives("M0,0 q10,10 20,0 t20,0 t20,0"), expected);
+}
+
+#[test]
+fn smooth_curves_without_a_matching_predecessor_start_at_the_current_point() {
+    assert_eq!(
+        primitives("M0,0 L10,0 S20,10 30,0")[2],
+        Primitive::Cubic {
+            start: point(10., 0.),
+            p1: point(10., 0.),
+            p2: point(20., 10.),
+            end: point(30., 0.),
+        }
+    );
+    // a quadratic control point is not mirrored into a cubic, nor the other way round
+    assert_eq!(
+        primitives("M0,0 Q5,5 10,0 S20,10 30,0")[2],
+        Primitive::Cubic {
+            start: point(10., 0.),
+            p1: point(10., 0.),
+            p2: point(20., 10.),
+            end: point(30., 0.),
+        }
+    );
+    assert_eq!(
+        primitives("M0,0 C0,5 10,5 10,0 T30,0")[2],
+        Primitive::Quadratic {
+            start: point(10., 0.),
+            p1: point(10., 0.),
+            end: point(30., 0.),
+        }
+    );
+}
+
+#[test]
+fn relative_arc_gets_its_center() {
+    match primitives("M5,5 a10,10 0 0,1 20,0")[1] {
+        Primitive::Arc {
+            start,
+            end,
+            center,
+            rx,
+            ry,
+            ..
+        } => {
+            assert_eq!(start, point(5., 5.));
+            assert_eq!(end, point(25., 5.));
+            assert!((center.x - 15.).abs() < 1e-9 && (center.y - 5.).abs() < 1e-9);
+            assert_eq!((rx, ry), (10., 10.));
+        }
+        other => panic!("expected an arc, got {:?}", other),
+    }
+}
+
+#[test]
+fn primitives_made_elsewhere_flatten_like_path_segments() {
+    // what a caller without a path parser hands over
+    let made = vec![
+        Primitive::Move { end: point(0., 0.) },
+        Primitive::Cubic {
+            start: point(0., 0.),
+            p1: point(10., 20.),
+            p2: point(30., 20.),
+            end: point(40., 0.),
+        },
+        Primitive::Line {
+            start: point(40., 0.),
+            end: point(40., 10.),
+        },
+    ];
+    let path = "M0,0 C10,20 30,20 40,0 V10";
+    assert_eq!(primitives(path), made);
+
+    let options = FlattenOptions::with_tolerance(0.01);
+    assert_eq!(
+        points_from_primitives(made.iter().copied(), options).collect::<Vec<_>>(),
+        points_from_path_segments_with_options(
+            PathParser::from(path).map(|segment| segment.unwrap()),
+            options
+        )
+        .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        arcs_from_primitives(made.into_iter(), 0.01).collect::<Vec<_>>(),
+        arcs_from_path_segments(PathParser::from(path).map(|segment| segment.unwrap()), 0.01)
+            .collect::<Vec<_>>()
+    );
+}
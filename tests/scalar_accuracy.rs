@@ -0,0 +1,124 @@
+use svg_to_lines::scalar::{Fixed, Scalar};
+use svg_to_lines::svg::flatten_options::{FlattenOptions, Flattening};
+use svg_to_lines::svg::svg_curve::{
+    points_from_path_segments, points_from_path_segments_with_options_as, LineTo,
+};
+use svgtypes::{PathParser, PathSegment};
+
+const PATH: &str = "M 10 20 L 110 40 h -30 v 55 \
+                    C 120 80 160 180 60 150 S 0 100 20 60 \
+                    Q 90 0 150 30 T 190 90 \
+                    A 60 30 25 1 0 100 170 a 20 20 0 0 1 -40 0 Z \
+                    m 50 -10 c 10 -30 40 -30 50 0";
+
+fn path() -> Vec<PathSegment> {
+    PathParser::from(PATH)
+        .map(|segment| segment.unwrap())
+        .collect()
+}
+
+fn flatten<S: Scalar>(options: FlattenOptions) -> Vec<LineTo<S>> {
+    points_from_path_segments_with_options_as(path().into_iter(), options).collect()
+}
+
+fn max_error<S: Scalar>(options: FlattenOptions) -> f64 {
+    let reference = flatten::<f64>(options);
+    let points = flatten::<S>(options);
+    assert_eq!(points.len(), reference.len());
+
+    reference
+        .iter()
+        .zip(&points)
+        .map(|(reference, line_to)| {
+            let point = line_to.target().cast::<f64>();
+            let expected = reference.target();
+            (point.x - expected.x)
+                .abs()
+                .max((point.y - expected.y).abs())
+        })
+        .fold(0., f64::max)
+}
+
+fn all_flattenings() -> Vec<FlattenOptions> {
+    vec![
+        FlattenOptions::default(),
+        FlattenOptions::with_tolerance(0.05),
+        FlattenOptions {
+            flattening: Flattening::FixedStep(0.05),
+            collapse_straight_curves: false,
+            ..FlattenOptions::default()
+        },
+    ]
+}
+
+#[test]
+fn plain_names_need_no_scalar_type() {
+    // would not compile if the scalar type had to be inferred from the use
+    let count = points_from_path_segments(path().into_iter()).count();
+    assert_eq!(count, flatten::<f64>(FlattenOptions::default()).len());
+}
+
+#[test]
+fn f32_points_follow_f64() {
+    for options in all_flattenings() {
+        let error = max_error::<f32>(options);
+        assert!(error < 1e-3, "{:?}: {}", options.flattening, error);
+    }
+}
+
+#[test]
+fn fixed_points_follow_f64() {
+    for options in all_flattenings() {
+        let error = max_error::<Fixed>(options);
+        // Q16.16 weights are good to about 1e-5, relative to curves of some 100 units
+        assert!(error < 1e-2, "{:?}: {}", options.flattening, error);
+    }
+}
+
+#[test]
+fn fixed_arithmetic_matches_f64() {
+    let values = [-300.25, -2.5, -0.001, 0., 0.75, 1., 3.3, 181., 1000.5];
+    for &a in &values {
+        let fixed_a = Fixed::from_f64(a);
+        assert!((fixed_a.to_f64() - a).abs() <= 1. / 131_072.);
+        for &b in &values {
+            let fixed_b = Fixed::from_f64(b);
+            assert!(((fixed_a + fixed_b).to_f64() - (a + b)).abs() < 1e-4);
+            assert!(((fixed_a - fixed_b).to_f64() - (a - b)).abs() < 1e-4);
+            if (a * b).abs() < 30_000. {
+                let error = ((fixed_a * fixed_b).to_f64() - a * b).abs();
+                assert!(error < 1e-4 * (1. + a.abs() + b.abs()), "{} * {}", a, b);
+            }
+            if b.abs() >= 0.5 {
+                assert!(
+                    ((fixed_a / fixed_b).to_f64() - a / b).abs() < 1e-3,
+                    "{} / {}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn fixed_functions_match_f64() {
+    for step in 0..=400 {
+        let x = step as f64 * 0.05 - 10.;
+        let fixed = Fixed::from_f64(x);
+        assert!((fixed.sin().to_f64() - x.sin()).abs() < 2e-4, "sin {}", x);
+        assert!((fixed.cos().to_f64() - x.cos()).abs() < 2e-4, "cos {}", x);
+
+        let root = Fixed::from_f64(x.abs() * 100.).sqrt().to_f64();
+        assert!((root - (x.abs() * 100.).sqrt()).abs() < 1e-4, "sqrt {}", x);
+    }
+}
+
+#[test]
+fn fixed_saturates_instead_of_wrapping() {
+    let big = Fixed::from_f64(30_000.);
+    assert_eq!(big + big, Fixed::MAX);
+    assert_eq!(big * -big, Fixed::MIN);
+    assert_eq!(Fixed::ONE / Fixed::ZERO, Fixed::MAX);
+    assert_eq!(Fixed::from_f64(1e12), Fixed::MAX);
+}
@@ -0,0 +1,142 @@
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::shapes::*;
+use svg_to_lines::svg::svg_curve::points_from_path_segments;
+use svgtypes::PathSegment;
+
+fn points(segments: impl Iterator<Item = PathSegment>) -> Vec<Point> {
+    points_from_path_segments(segments)
+        .map(|line_to| line_to.target())
+        .collect()
+}
+
+// the radii of every arc of the shape
+fn radii(segments: impl Iterator<Item = PathSegment>) -> Vec<(f64, f64)> {
+    segments
+        .filter_map(|segment| match segment {
+            PathSegment::EllipticalArc { rx, ry, .. } => Some((rx, ry)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn missing_rect_radii_default_to_each_other() {
+    let rect = |rx, ry| radii(rect_segments(0., 0., 100., 50., rx, ry));
+    assert_eq!(rect(Some(10.), None), vec![(10., 10.); 4]);
+    assert_eq!(rect(None, Some(5.)), vec![(5., 5.); 4]);
+    assert_eq!(rect(Some(10.), Some(5.)), vec![(10., 5.); 4]);
+    // a negative radius counts as missing
+    assert_eq!(rect(Some(-1.), Some(5.)), vec![(5., 5.); 4]);
+    assert_eq!(rect(None, None), vec![]);
+}
+
+#[test]
+fn rect_radii_are_limited_to_half_the_size() {
+    let rect = |rx, ry| radii(rect_segments(0., 0., 100., 50., rx, ry));
+    assert_eq!(rect(Some(80.), Some(40.)), vec![(50., 25.); 4]);
+    assert_eq!(rect(Some(30.), None), vec![(30., 25.); 4]);
+}
+
+#[test]
+fn rounded_corners_stay_inside_the_rect() {
+    for (rx, ry) in [(10., 10.), (30., 5.), (80., 80.)] {
+        let points = points(rect_segments(20., 10., 100., 50., Some(rx), Some(ry)));
+        assert!(points.len() > 8);
+        for point in points {
+            assert!(
+                (20. - 1e-9..=120. + 1e-9).contains(&point.x)
+                    && (10. - 1e-9..=60. + 1e-9).contains(&point.y),
+                "{:?} with radii {} and {}",
+                point,
+                rx,
+                ry
+            );
+        }
+    }
+}
+
+#[test]
+fn circle_and_ellipse_points_lie_on_the_curve() {
+    let on_ellipse = |points: Vec<Point>, cx: f64, cy: f64, rx: f64, ry: f64| {
+        assert!(points.len() > 4);
+        for point in points {
+            let x = (point.x - cx) / rx;
+            let y = (point.y - cy) / ry;
+            assert!((x * x + y * y - 1.).abs() < 1e-6, "{:?}", point);
+        }
+    };
+    on_ellipse(points(circle_segments(50., 40., 30.)), 50., 40., 30., 30.);
+    on_ellipse(
+        points(ellipse_segments(-10., 5., 40., 15.)),
+        -10.,
+        5.,
+        40.,
+        15.,
+    );
+
+    // starting at the rightmost point and going round clockwise, down first
+    let circle = points(circle_segments(0., 0., 10.));
+    assert_eq!(circle[0], Point::new(10., 0.));
+    assert!(circle[1].y > 0.);
+    assert_eq!(circle.last(), Some(&Point::new(10., 0.)));
+}
+
+#[test]
+fn zero_and_negative_sizes_give_no_segments() {
+    assert_eq!(rect_segments(0., 0., 0., 10., None, None).count(), 0);
+    assert_eq!(rect_segments(0., 0., 10., -5., Some(1.), None).count(), 0);
+    assert_eq!(circle_segments(0., 0., 0.).count(), 0);
+    assert_eq!(circle_segments(0., 0., -3.).count(), 0);
+    assert_eq!(ellipse_segments(0., 0., 10., 0.).count(), 0);
+    assert_eq!(ellipse_segments(0., 0., -1., 10.).count(), 0);
+    assert_eq!(ellipse_segments(0., 0., f64::NAN, 10.).count(), 0);
+}
+
+#[test]
+fn polygons_close_and_polylines_do_not() {
+    let polyline: Vec<_> = polyline_segments("0,0 10,0 10,10").collect();
+    assert_eq!(
+        polyline,
+        vec![
+            PathSegment::MoveTo {
+                abs: true,
+                x: 0.,
+                y: 0.
+            },
+            PathSegment::LineTo {
+                abs: true,
+                x: 10.,
+                y: 0.
+            },
+            PathSegment::LineTo {
+                abs: true,
+                x: 10.,
+                y: 10.
+            },
+        ]
+    );
+
+    let polygon: Vec<_> = polygon_segments("0,0 10,0 10,10").collect();
+    assert_eq!(polygon[..3], polyline[..]);
+    assert_eq!(polygon[3..], [PathSegment::ClosePath { abs: true }]);
+    assert_eq!(
+        points(polygon.into_iter()).last(),
+        Some(&Point::new(0., 0.))
+    );
+
+    // nothing to close without points
+    assert_eq!(polygon_segments("").count(), 0);
+}
+
+#[test]
+fn an_odd_coordinate_list_drops_its_last_number() {
+    let even: Vec<_> = polyline_segments("0,0 10,0 10,10").collect();
+    assert_eq!(
+        polyline_segments("0,0 10,0 10,10 5").collect::<Vec<_>>(),
+        even
+    );
+    assert_eq!(
+        polygon_segments("0 0 10 0 10 10 5").count(),
+        polygon_segments("0 0 10 0 10 10").count()
+    );
+}
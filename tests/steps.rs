@@ -0,0 +1,71 @@
+use std::f64::consts::PI;
+
+use svg_to_lines::robot::steps::{BuildUp, Drive, StepConverter};
+
+// a spool that moves 10 units of line per turn, on a 200 step motor at 16 microsteps
+fn drive() -> Drive {
+    Drive::new(10. / PI, 200, 16)
+}
+
+#[test]
+fn steps_follow_the_spool_circumference_and_gearing() {
+    assert_eq!(drive().steps_per_turn(), 3200.);
+    let mut converter = StepConverter::new([drive(), drive()], [100., 200.]);
+    assert_eq!(converter.targets([101., 195.]), [320, -1600]);
+    assert_eq!(converter.targets([100., 200.]), [0, 0]);
+
+    let geared = Drive {
+        gear_ratio: 2.5,
+        ..drive()
+    };
+    let mut converter = StepConverter::new([geared], [100.]);
+    assert_eq!(converter.targets([101.]), [800]);
+    assert_eq!(converter.positions(), [800]);
+}
+
+#[test]
+fn rounding_does_not_add_up() {
+    let mut converter = StepConverter::new([drive()], [0.]);
+    // a thousandth of a unit is 0.32 steps, each move on its own rounds to nothing
+    for step in 1..=1000 {
+        let length = step as f64 * 0.001;
+        let [position] = converter.targets([length]);
+        let exact = length * 320.;
+        assert!((position as f64 - exact).abs() <= 0.5 + 1e-9);
+        assert!(converter.residuals()[0].abs() <= 0.5 + 1e-9);
+        assert!((position as f64 + converter.residuals()[0] - exact).abs() < 1e-6);
+    }
+    assert_eq!(converter.positions(), [320]);
+}
+
+#[test]
+fn wound_line_makes_the_spool_thicker() {
+    // ten turns to a layer of 0.5 thick line on a spool 10 across
+    let build_up = BuildUp {
+        line_diameter: 0.5,
+        spool_width: 5.,
+        line_length: 2000.,
+    };
+    let drive = Drive {
+        build_up: Some(build_up),
+        ..Drive::new(10., 200, 1)
+    };
+
+    // with all of it off the spool the first layer winds at 10.5 across
+    let empty = drive.turns(2000.) - drive.turns(1990.);
+    assert!((empty - 10. / (PI * 10.5)).abs() < 1e-9);
+
+    // the first layer holds 10 turns at 10.5 across, the second winds at 11.5
+    let first_layer = 10. * PI * 10.5;
+    let length = 2000. - first_layer;
+    let second = drive.turns(length) - drive.turns(length - 10.);
+    assert!((second - 10. / (PI * 11.5)).abs() < 1e-9);
+
+    // so the same length of line takes fewer steps the more is wound on
+    let mut converter = StepConverter::new([drive], [2000.]);
+    let [near_empty] = converter.targets([1990.]);
+    let [before] = converter.targets([length]);
+    let [after] = converter.targets([length - 10.]);
+    assert!(near_empty < 0 && after < before);
+    assert!(-near_empty > before - after);
+}
@@ -0,0 +1,66 @@
+use svg_to_lines::svg::flatten_options::FlattenOptions;
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::svg_curve::{points_from_path_segments_with_options, LineTo};
+use svgtypes::PathParser;
+
+fn drawn_points(path: &str, straight_tolerance: f64) -> Vec<Point> {
+    let options = FlattenOptions {
+        straight_tolerance,
+        ..FlattenOptions::with_tolerance(0.01)
+    };
+    let segments = PathParser::from(path).map(|segment| segment.unwrap());
+    points_from_path_segments_with_options(segments, options)
+        .filter_map(|line_to| match line_to {
+            LineTo::Draw(point) => Some(point),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_collapsed(path: &str, straight_tolerance: f64) -> bool {
+    drawn_points(path, straight_tolerance).len() == 1
+}
+
+#[test]
+fn horizontal_chord() {
+    assert!(is_collapsed("M0,0 Q5,0 10,0", 0.05));
+    assert!(is_collapsed("M0,0 C3,0.04 7,-0.04 10,0", 0.05));
+    assert!(!is_collapsed("M0,0 Q5,0.2 10,0", 0.05));
+}
+
+#[test]
+fn vertical_chord() {
+    assert!(is_collapsed("M3,0 Q3,5 3,10", 0.05));
+    assert!(is_collapsed("M3,0 C3.04,3 2.96,7 3,10", 0.05));
+    assert!(!is_collapsed("M3,0 Q3.2,5 3,10", 0.05));
+}
+
+#[test]
+fn diagonal_chord() {
+    assert!(is_collapsed("M0,0 C2,2 8,8 10,10", 0.05));
+    assert!(!is_collapsed("M0,0 C2,2.5 8,8 10,10", 0.05));
+}
+
+#[test]
+fn control_point_beyond_chord_is_not_straight() {
+    assert!(!is_collapsed("M0,0 Q15,0 10,0", 0.05));
+    assert!(!is_collapsed("M0,0 C-5,0 15,0 10,0", 0.05));
+    assert!(!is_collapsed("M0,0 Q0,-3 0,10", 0.05));
+    // an overshoot within the tolerance of the end still counts
+    assert!(is_collapsed("M0,0 Q10.04,0 10,0", 0.05));
+    assert!(is_collapsed("M0,0 C-0.03,0 10.03,0 10,0", 0.05));
+}
+
+#[test]
+fn zero_length_chord() {
+    assert!(drawn_points("M2,2 Q2,2 2,2", 0.05).is_empty());
+    assert!(drawn_points("M2,2 C2.01,2 2,2.01 2,2", 0.05).is_empty());
+    assert!(drawn_points("M2,2 C5,5 -1,5 2,2", 0.05).len() > 1);
+}
+
+#[test]
+fn tolerance_is_in_output_units() {
+    assert!(is_collapsed("M0,0 Q50,0.5 100,0", 1.));
+    assert!(!is_collapsed("M0,0 Q500,5 1000,0", 1.));
+    assert!(!is_collapsed("M0,0 Q50,0.5 100,0", 0.1));
+}
@@ -0,0 +1,230 @@
+use svg_to_lines::robot::four_cable::{Cable, FourCable};
+use svg_to_lines::robot::kinematics::Kinematics;
+use svg_to_lines::robot::subdivide::{subdivide_line_tos, SubdivideOptions};
+use svg_to_lines::robot::v_plotter::VPlotter;
+use svg_to_lines::svg::line_to::{CircularArc, LineTo};
+use svg_to_lines::svg::math::distance_to_segment;
+use svg_to_lines::svg::point::Point;
+
+fn plotter() -> VPlotter {
+    VPlotter::new(Point::new(0., 0.), Point::new(1000., 0.), 0.)
+}
+
+// a 1000 x 1000 frame with the lines leaving from its corners, tied to the pen tip
+fn frame() -> FourCable {
+    let cable = |x, y| Cable {
+        anchor: Point::new(x, y),
+        attachment: Point::ZERO,
+    };
+    FourCable::new([
+        cable(0., 0.),
+        cable(1000., 0.),
+        cable(1000., 1000.),
+        cable(0., 1000.),
+    ])
+}
+
+// with the pen starting where the first move goes
+fn subdivide_for<K: Kinematics<MOTORS>, const MOTORS: usize>(
+    kinematics: &K,
+    line_tos: Vec<LineTo>,
+    options: SubdivideOptions,
+) -> Vec<LineTo> {
+    let start = line_tos[0].target();
+    subdivide_line_tos(kinematics, line_tos.into_iter(), start, options).collect()
+}
+
+fn subdivide(line_tos: Vec<LineTo>, options: SubdivideOptions) -> Vec<LineTo> {
+    subdivide_for(&plotter(), line_tos, options)
+}
+
+// the farthest the pen gets from each move, at a few places along it, with the motors
+// changing the line lengths evenly
+fn largest_deviation_for<K: Kinematics<MOTORS>, const MOTORS: usize>(
+    kinematics: &K,
+    line_tos: &[LineTo],
+) -> f64 {
+    let mut largest: f64 = 0.;
+    for ends in line_tos.windows(2) {
+        let (start, end) = (ends[0].target(), ends[1].target());
+        let from = kinematics.lengths(start).unwrap();
+        let to = kinematics.lengths(end).unwrap();
+        for sample in 1..10 {
+            let time = sample as f64 / 10.;
+            let even: [f64; MOTORS] =
+                std::array::from_fn(|motor| from[motor] + (to[motor] - from[motor]) * time);
+            let pen = kinematics.pen_near(even, start).unwrap();
+            largest = largest.max(distance_to_segment(start, end, pen));
+        }
+    }
+    largest
+}
+
+fn largest_deviation(line_tos: &[LineTo]) -> f64 {
+    largest_deviation_for(&plotter(), line_tos)
+}
+
+#[test]
+fn long_draws_are_split_evenly_within_the_tolerance() {
+    let line_tos = vec![
+        LineTo::Fly(Point::new(100., 200.)),
+        LineTo::Draw(Point::new(900., 200.)),
+    ];
+    let options = SubdivideOptions {
+        tolerance: 0.1,
+        fly_moves: false,
+    };
+    let subdivided = subdivide(line_tos.clone(), options);
+    assert!(subdivided.len() > 2, "{:?}", subdivided);
+    assert_eq!(subdivided[0], line_tos[0]);
+    assert_eq!(subdivided.last(), line_tos.last());
+    assert!(subdivided[1..]
+        .iter()
+        .all(|line_to| matches!(line_to, LineTo::Draw(_))));
+    assert!(largest_deviation(&line_tos) > 0.1);
+    assert!(largest_deviation(&subdivided) <= 0.1);
+
+    // evenly spaced along the straight line
+    let pieces = subdivided.len() - 1;
+    for (piece, line_to) in subdivided.iter().enumerate() {
+        let expected = 100. + 800. * piece as f64 / pieces as f64;
+        assert!((line_to.target().x - expected).abs() < 1e-9);
+        assert_eq!(line_to.target().y, 200.);
+    }
+
+    // a tighter tolerance takes more pieces
+    let tighter = subdivide(
+        line_tos,
+        SubdivideOptions {
+            tolerance: 0.01,
+            ..options
+        },
+    );
+    assert!(tighter.len() > subdivided.len());
+    assert!(largest_deviation(&tighter) <= 0.01);
+}
+
+#[test]
+fn fly_moves_are_only_split_when_asked() {
+    let line_tos = vec![
+        LineTo::Fly(Point::new(100., 200.)),
+        LineTo::Fly(Point::new(900., 200.)),
+    ];
+    assert_eq!(
+        subdivide(line_tos.clone(), SubdivideOptions::default()),
+        line_tos
+    );
+
+    let subdivided = subdivide(
+        line_tos,
+        SubdivideOptions {
+            fly_moves: true,
+            ..SubdivideOptions::default()
+        },
+    );
+    assert!(subdivided.len() > 2);
+    assert!(subdivided
+        .iter()
+        .all(|line_to| matches!(line_to, LineTo::Fly(_))));
+}
+
+#[test]
+fn arcs_short_moves_and_unreachable_ends_pass_unchanged() {
+    let arc = LineTo::Arc(CircularArc {
+        end: Point::new(900., 200.),
+        center: Point::new(500., 200.),
+        radius: 400.,
+        clockwise: true,
+    });
+    let line_tos = vec![
+        LineTo::Fly(Point::new(100., 200.)),
+        arc,
+        // short enough to be straight within the tolerance
+        LineTo::Draw(Point::new(901., 200.)),
+        // above the motors
+        LineTo::Draw(Point::new(500., -100.)),
+    ];
+    assert_eq!(
+        subdivide(line_tos.clone(), SubdivideOptions::default()),
+        line_tos
+    );
+}
+
+#[test]
+fn the_pen_is_found_again_from_its_line_lengths() {
+    let plotter = plotter();
+    for pen in [
+        Point::new(500., 500.),
+        Point::new(120., 300.),
+        Point::new(870., 40.),
+    ] {
+        let lengths = plotter.lengths(pen).unwrap();
+        let found = plotter.pen_near(lengths, Point::new(500., 400.)).unwrap();
+        assert!(
+            (found - pen).x.abs() < 1e-6 && (found - pen).y.abs() < 1e-6,
+            "{:?}",
+            found
+        );
+    }
+    // lines that do not reach each other give the nearest the pen gets to both lengths
+    let closest = plotter
+        .pen_near([100., 100.], Point::new(500., 400.))
+        .unwrap();
+    assert!((closest.x - 500.).abs() < 1e-6, "{:?}", closest);
+    assert!(closest.y > 0. && closest.y < 100., "{:?}", closest);
+    // nothing to start from out of reach
+    assert_eq!(plotter.pen_near([100., 100.], Point::new(500., -10.)), None);
+}
+
+#[test]
+fn four_cable_moves_are_split_within_the_tolerance() {
+    let frame = frame();
+    let line_tos = vec![
+        LineTo::Fly(Point::new(200., 200.)),
+        LineTo::Draw(Point::new(800., 300.)),
+    ];
+    assert!(largest_deviation_for(&frame, &line_tos) > 0.1);
+    for tolerance in [0.1, 0.01] {
+        let options = SubdivideOptions {
+            tolerance,
+            fly_moves: false,
+        };
+        let subdivided = subdivide_for(&frame, line_tos.clone(), options);
+        assert!(subdivided.len() > 2, "{:?}", subdivided);
+        assert!(subdivided.len() < 200, "{}", subdivided.len());
+        assert_eq!(subdivided.last(), line_tos.last());
+        assert!(largest_deviation_for(&frame, &subdivided) <= tolerance);
+    }
+
+    // through the middle, along the diagonals, the lines change evenly already
+    let diagonal = vec![
+        LineTo::Fly(Point::new(100., 100.)),
+        LineTo::Draw(Point::new(900., 900.)),
+    ];
+    assert!(largest_deviation_for(&frame, &diagonal) < 1e-6);
+}
+
+#[test]
+fn four_cable_pens_are_found_from_lengths_that_do_not_agree() {
+    let frame = frame();
+    let (start, end) = (Point::new(200., 200.), Point::new(800., 300.));
+    let (from, to) = (frame.lengths(start).unwrap(), frame.lengths(end).unwrap());
+    for time in [0.25, 0.5, 0.75] {
+        let even: [f64; 4] =
+            std::array::from_fn(|motor| from[motor] + (to[motor] - from[motor]) * time);
+        let straight = start + (end - start) * time;
+        let pen = frame.pen_near(even, straight).unwrap();
+        // the best fit is better than the straight point, and not far from it
+        let misfit = |at: [f64; 4]| {
+            (0..4)
+                .map(|motor| (at[motor] - even[motor]).powi(2))
+                .sum::<f64>()
+        };
+        assert!(misfit(frame.lengths(pen).unwrap()) < misfit(frame.lengths(straight).unwrap()));
+        assert!(distance_to_segment(start, end, pen) < 100., "{:?}", pen);
+    }
+    let pen = Point::new(640., 260.);
+    let found = frame.pen_near(frame.lengths(pen).unwrap(), Point::new(500., 500.));
+    let found = found.unwrap();
+    assert!((found.x - pen.x).abs() < 1e-6 && (found.y - pen.y).abs() < 1e-6);
+}
@@ -0,0 +1,76 @@
+use svg_to_lines::svg::math::{distance_to_segment, CubicCurve, CurvePoint, SquareCurve};
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::svg_curve::points_from_path_segments_with_tolerance;
+use svgtypes::PathParser;
+
+const SAMPLES: usize = 2000;
+
+fn polyline(path: &str, tolerance: f64) -> Vec<Point> {
+    let segments = PathParser::from(path).map(|segment| segment.unwrap());
+    points_from_path_segments_with_tolerance(segments, tolerance)
+        .map(|line_to| line_to.target())
+        .collect()
+}
+
+// the farthest any point of the real curve is from the flattened one
+fn max_deviation(points: &[Point], curve: impl Fn(f64) -> Point) -> f64 {
+    (0..=SAMPLES)
+        .map(|sample| curve(sample as f64 / SAMPLES as f64))
+        .map(|on_curve| {
+            points
+                .windows(2)
+                .map(|chord| distance_to_segment(chord[0], chord[1], on_curve))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .fold(0., f64::max)
+}
+
+#[test]
+fn quadratic_stays_within_tolerance() {
+    let curve = SquareCurve::new(
+        Point::new(0., 0.),
+        Point::new(50., 80.),
+        Point::new(100., 0.),
+    );
+    for tolerance in [1., 0.1, 0.01] {
+        let points = polyline("M0,0 Q50,80 100,0", tolerance);
+        assert!(max_deviation(&points, |time| curve.at(time)) <= tolerance * 1.001);
+    }
+}
+
+#[test]
+fn cubic_stays_within_tolerance() {
+    let curve = CubicCurve::new(
+        Point::new(0., 0.),
+        Point::new(10., 90.),
+        Point::new(90., -60.),
+        Point::new(100., 30.),
+    );
+    for tolerance in [1., 0.1, 0.01] {
+        let points = polyline("M0,0 C10,90 90,-60 100,30", tolerance);
+        assert!(max_deviation(&points, |time| curve.at(time)) <= tolerance * 1.001);
+    }
+}
+
+#[test]
+fn arc_stays_within_tolerance() {
+    // the lower half of the circle of radius 40 around the origin, y pointing down
+    let circle = |time: f64| {
+        let angle = time * std::f64::consts::PI;
+        Point::new(40. * angle.cos(), 40. * angle.sin())
+    };
+    for tolerance in [1., 0.1, 0.01] {
+        let points = polyline("M40,0 A40,40 0 0,1 -40,0", tolerance);
+        assert!(max_deviation(&points, circle) <= tolerance * 1.001);
+    }
+}
+
+#[test]
+fn point_count_grows_with_curve_size() {
+    let small = polyline("M0,0 C1,9 9,-6 10,3", 0.01).len();
+    let large = polyline("M0,0 C100,900 900,-600 1000,300", 0.01).len();
+    // chords are as long as the square root of the size allows: 100 times the size
+    // takes about 10 times the points
+    assert!(small > 2);
+    assert!(large > 5 * small && large < 20 * small);
+}
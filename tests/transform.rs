@@ -0,0 +1,142 @@
+use svg_to_lines::svg::math::{distance, CurvePoint, EllipseCurve};
+use svg_to_lines::svg::point::Point;
+use svg_to_lines::svg::primitive::{primitives_from_path_segments, Primitive};
+use svg_to_lines::svg::transform::Transform;
+use svgtypes::PathParser;
+
+const SAMPLES: usize = 64;
+
+fn arc(path: &str) -> Primitive {
+    let segments = PathParser::from(path).map(|segment| segment.unwrap());
+    primitives_from_path_segments(segments)
+        .find(|primitive| matches!(primitive, Primitive::Arc { .. }))
+        .unwrap()
+}
+
+fn curve(arc: Primitive) -> EllipseCurve {
+    match arc {
+        Primitive::Arc {
+            center,
+            rx,
+            ry,
+            x_rotation,
+            start_angle,
+            sweep_angle,
+            ..
+        } => EllipseCurve::new(
+            start_angle,
+            sweep_angle,
+            rx,
+            ry,
+            x_rotation,
+            center.x,
+            center.y,
+        ),
+        other => panic!("expected an arc, got {:?}", other),
+    }
+}
+
+// the transformed arc has to go through the transformed points of the original one, in
+// the same order
+fn assert_arc_follows(path: &str, transform: Transform) {
+    let original = arc(path);
+    let transformed = original.transformed(&transform);
+    let (before, after) = (curve(original), curve(transformed));
+    for sample in 0..=SAMPLES {
+        let time = sample as f64 / SAMPLES as f64;
+        let expected = transform.apply(before.at(time));
+        let point = after.at(time);
+        assert!(
+            distance(point, expected) < 1e-9,
+            "{} through {:?} at {}: {:?} instead of {:?}",
+            path,
+            transform,
+            time,
+            point,
+            expected
+        );
+    }
+    match transformed {
+        Primitive::Arc { start, end, .. } => {
+            assert!(distance(after.at(0.), start) < 1e-9);
+            assert!(distance(after.at(1.), end) < 1e-9);
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn transforms() -> Vec<Transform> {
+    vec![
+        Transform::translate(30., -12.),
+        Transform::scale(2., 0.5),
+        Transform::rotate(35.),
+        Transform::skew_x(20.).then(&Transform::rotate(-70.)),
+        Transform::new(1.5, 0.3, -0.4, 0.8, 7., 9.),
+        // mirrored ones turn the sweep direction around
+        Transform::scale(-1., 1.),
+        Transform::scale(1., -3.),
+        Transform::rotate(60.).then(&Transform::scale(-2., 1.)),
+        Transform::new(0.2, 1.1, 0.9, -0.4, -5., 3.),
+    ]
+}
+
+#[test]
+fn transformed_arcs_follow_the_transformed_points() {
+    let paths = [
+        "M10,0 A10,10 0 0,1 0,10",
+        "M60,0 A60,20 0 0,1 -60,0",
+        "M0,0 A40,15 30 1,0 50,20",
+        "M5,5 a25,50 -80 1,1 10,-30",
+    ];
+    for path in paths {
+        for transform in transforms() {
+            assert_arc_follows(path, transform);
+        }
+    }
+}
+
+#[test]
+fn mirroring_flips_the_sweep() {
+    let sweep = |primitive| match primitive {
+        Primitive::Arc { sweep_angle, .. } => sweep_angle,
+        _ => unreachable!(),
+    };
+    let original = arc("M10,0 A10,10 0 0,1 0,10");
+    assert!(sweep(original) > 0.);
+    assert!(sweep(original.transformed(&Transform::scale(-1., 1.))) < 0.);
+    assert!(sweep(original.transformed(&Transform::scale(-1., -1.))) > 0.);
+}
+
+#[test]
+fn bezier_control_points_are_mapped() {
+    let segments = PathParser::from("M0,0 C10,0 20,10 20,20").map(|segment| segment.unwrap());
+    let cubic = primitives_from_path_segments(segments).nth(1).unwrap();
+    let transform = Transform::new(0., 1., -1., 0., 5., 5.);
+    assert_eq!(
+        cubic.transformed(&transform),
+        Primitive::Cubic {
+            start: Point::new(5., 5.),
+            p1: Point::new(5., 15.),
+            p2: Point::new(-5., 25.),
+            end: Point::new(-15., 25.),
+        }
+    );
+}
+
+#[test]
+fn inverses_undo_the_transform() {
+    let transform = Transform::rotate(30.)
+        .then(&Transform::skew_x(20.))
+        .then(&Transform::scale(2., -0.5))
+        .then(&Transform::translate(7., -3.));
+    let inverse = transform.inverse().unwrap();
+    for point in [
+        Point::new(0., 0.),
+        Point::new(12., -5.),
+        Point::new(-3., 40.),
+    ] {
+        assert!(distance(inverse.apply(transform.apply(point)), point) < 1e-9);
+        assert!(distance(transform.apply(inverse.apply(point)), point) < 1e-9);
+    }
+    assert_eq!(Transform::scale(1., 0.).inverse(), None);
+}
@@ -0,0 +1,99 @@
+use svg_to_lines::robot::kinematics::{Kinematics, Unreachable};
+use svg_to_lines::robot::v_plotter::{Sag, VPlotter};
+use svg_to_lines::svg::line_to::LineTo;
+use svg_to_lines::svg::point::Point;
+
+// motors 1000 apart at the top of the board, a gondola 40 wide
+fn plotter() -> VPlotter {
+    VPlotter::new(Point::new(0., 0.), Point::new(1000., 0.), 40.)
+}
+
+fn assert_near(lengths: [f64; 2], expected: [f64; 2]) {
+    for (length, expected) in lengths.iter().zip(expected.iter()) {
+        assert!(
+            (length - expected).abs() < 1e-9,
+            "{:?} instead of {:?}",
+            lengths,
+            expected
+        );
+    }
+}
+
+#[test]
+fn lines_end_at_the_sides_of_the_gondola() {
+    let plotter = plotter();
+    assert_near(
+        plotter.line_lengths(Point::new(500., 480.)).unwrap(),
+        [480f64.hypot(480.), 480f64.hypot(480.)],
+    );
+    assert_near(
+        plotter.lengths(Point::new(320., 300.)).unwrap(),
+        [300f64.hypot(300.), 660f64.hypot(300.)],
+    );
+}
+
+#[test]
+fn the_gondola_hangs_below_and_between_the_motors() {
+    let mut plotter = plotter();
+    assert!(plotter.is_reachable(Point::new(500., 10.)));
+    assert!(!plotter.is_reachable(Point::new(500., 0.)));
+    assert!(!plotter.is_reachable(Point::new(500., -10.)));
+    // the left end of the gondola would be left of the left motor
+    assert_eq!(plotter.line_lengths(Point::new(15., 500.)), None);
+    assert!(plotter.is_reachable(Point::new(25., 500.)));
+
+    plotter.margin = 50.;
+    assert!(!plotter.is_reachable(Point::new(500., 40.)));
+    assert!(!plotter.is_reachable(Point::new(60., 500.)));
+    assert!(plotter.is_reachable(Point::new(80., 500.)));
+}
+
+#[test]
+fn sagging_lines_are_longer_the_more_they_weigh() {
+    let mut plotter = plotter();
+    let pen = Point::new(300., 400.);
+    let taut = plotter.line_lengths(pen).unwrap();
+
+    plotter.sag = Some(Sag {
+        line_weight: 0.,
+        gondola_weight: 500.,
+    });
+    assert_near(plotter.line_lengths(pen).unwrap(), taut);
+
+    plotter.sag = Some(Sag {
+        line_weight: 0.01,
+        gondola_weight: 500.,
+    });
+    let light = plotter.line_lengths(pen).unwrap();
+    plotter.sag = Some(Sag {
+        line_weight: 0.05,
+        gondola_weight: 500.,
+    });
+    let heavy = plotter.line_lengths(pen).unwrap();
+    for motor in 0..2 {
+        assert!(taut[motor] < light[motor], "{:?} {:?}", taut, light);
+        assert!(light[motor] < heavy[motor], "{:?} {:?}", light, heavy);
+        // still only a small correction
+        assert!(heavy[motor] - taut[motor] < 1., "{:?} {:?}", taut, heavy);
+    }
+}
+
+#[test]
+fn moves_out_of_reach_are_reported_with_their_index() {
+    let line_tos = vec![
+        LineTo::Fly(Point::new(500., 500.)),
+        LineTo::Draw(Point::new(500., -5.)),
+    ];
+    let lengths: Vec<_> = plotter().lengths_for_moves(line_tos.into_iter()).collect();
+    assert_near(
+        lengths[0].unwrap(),
+        [480f64.hypot(500.), 480f64.hypot(500.)],
+    );
+    assert_eq!(
+        lengths[1],
+        Err(Unreachable {
+            index: 1,
+            point: Point::new(500., -5.),
+        })
+    );
+}